@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+
+/// The usage message shown when the command line arguments are invalid
+const USAGE: &str = "Usage: adventurers validate <map file> [q1|q2|q3|quest file] [--config <config file>]
+       adventurers edit <map file> [--config <config file>]
+       adventurers generate <new map file> [--seed <seed>] [--size <width>x<height>]
+       adventurers convert <map file> <new map file>
+       adventurers import <Tiled map file> <tileset mapping file> <new map file>
+       adventurers solve <map file> <q1|q2|q3|quest file> [--max-states <count>] [--config <config file>]
+       adventurers <map file> <q1|q2|q3|quest file> [--load <save file>] [--script <moves file>] [--record <recording file>] [--jsonl <event log file>] [--config <config file>] [--replay <recording file> [--speed <factor>]]";
+
+/// The number of states the solver explores before giving up, by default
+const DEFAULT_MAX_STATES: usize = 500_000;
+
+/// The size of generated maps, by default, which is the size of the shipped maps
+const DEFAULT_GENERATE_SIZE: (i32, i32) = (80, 40);
+
+/// The save file used when the game was not loaded from one
+const DEFAULT_SAVE_FILE: &str = "adventurers_save.ron";
+
+/// What the game has been asked to do on the command line
+pub enum Command {
+    /// Play the game
+    Play(Args),
+    /// Check a map, and optionally a quest, for problems
+    Validate {
+        /// The file name of the map
+        map: String,
+        /// The name of a built-in quest, or the file name of a quest
+        quest: Option<String>,
+        /// The file name of the config file, instead of the default one
+        config: Option<String>,
+    },
+    /// Edit a map file in the terminal
+    Edit {
+        /// The file name of the map, which is created if it doesn't exist
+        map: String,
+        /// The file name of the config file, instead of the default one
+        config: Option<String>,
+    },
+    /// Generate a map from a seed
+    Generate {
+        /// The file name to write the map to
+        output: String,
+        /// The seed the map is generated from, or `None` to choose one
+        seed: Option<u64>,
+        /// The number of columns and rows of the map
+        size: (i32, i32),
+    },
+    /// Convert a map file to the format of a new map file, given by its extension
+    Convert {
+        /// The file name of the map
+        map: String,
+        /// The file name to write the converted map to
+        output: String,
+    },
+    /// Import a map laid out in the Tiled editor, writing it in the format of a
+    /// new map file
+    Import {
+        /// The file name of the Tiled map
+        tiled: String,
+        /// The file name of the tileset mapping, giving the block of each tile
+        mapping: String,
+        /// The file name to write the imported map to
+        output: String,
+    },
+    /// Search for moves that complete a quest on a map
+    Solve {
+        /// The file name of the map
+        map: String,
+        /// The name of a built-in quest, or the file name of a quest
+        quest: String,
+        /// The number of states to explore before giving up
+        max_states: usize,
+        /// The file name of the config file, instead of the default one
+        config: Option<String>,
+    },
+}
+
+impl Command {
+    /// Parse the command line arguments
+    ///
+    /// # Errors
+    ///
+    /// If the arguments of the command are invalid
+    pub fn parse() -> Result<Command, Box<dyn Error>> {
+        let mut args = env::args().skip(1).peekable();
+
+        if args.peek().map(String::as_str) == Some("validate") {
+            args.next();
+            let (positional, mut options) = parse_options(args, &["--config"])?;
+            let mut positional = positional.into_iter();
+            let map = positional
+                .next()
+                .ok_or(format!("You failed to provide a map filename\n{USAGE}"))?;
+            let quest = positional.next();
+            no_more(positional)?;
+            return Ok(Command::Validate {
+                map,
+                quest,
+                config: options.remove("--config"),
+            });
+        }
+
+        if args.peek().map(String::as_str) == Some("edit") {
+            args.next();
+            let (positional, mut options) = parse_options(args, &["--config"])?;
+            let mut positional = positional.into_iter();
+            let map = positional
+                .next()
+                .ok_or(format!("You failed to provide a map filename\n{USAGE}"))?;
+            no_more(positional)?;
+            return Ok(Command::Edit {
+                map,
+                config: options.remove("--config"),
+            });
+        }
+
+        if args.peek().map(String::as_str) == Some("generate") {
+            args.next();
+            let (positional, mut options) = parse_options(args, &["--seed", "--size"])?;
+            let mut positional = positional.into_iter();
+            let output = positional
+                .next()
+                .ok_or(format!("You failed to provide a new map filename\n{USAGE}"))?;
+            no_more(positional)?;
+            let seed = options
+                .remove("--seed")
+                .map(|seed| seed.parse())
+                .transpose()
+                .map_err(|_| format!("--seed requires a whole number\n{USAGE}"))?;
+            let size = match options.remove("--size") {
+                Some(size) => size
+                    .split_once('x')
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+                    .ok_or(format!("--size requires a size like 80x40\n{USAGE}"))?,
+                None => DEFAULT_GENERATE_SIZE,
+            };
+            return Ok(Command::Generate { output, seed, size });
+        }
+
+        if args.peek().map(String::as_str) == Some("convert") {
+            args.next();
+            let map = args
+                .next()
+                .ok_or(format!("You failed to provide a map filename\n{USAGE}"))?;
+            let output = args
+                .next()
+                .ok_or(format!("You failed to provide a new map filename\n{USAGE}"))?;
+            no_more(args)?;
+            return Ok(Command::Convert { map, output });
+        }
+
+        if args.peek().map(String::as_str) == Some("import") {
+            args.next();
+            let tiled = args.next().ok_or(format!(
+                "You failed to provide a Tiled map filename\n{USAGE}"
+            ))?;
+            let mapping = args.next().ok_or(format!(
+                "You failed to provide a tileset mapping filename\n{USAGE}"
+            ))?;
+            let output = args
+                .next()
+                .ok_or(format!("You failed to provide a new map filename\n{USAGE}"))?;
+            no_more(args)?;
+            return Ok(Command::Import {
+                tiled,
+                mapping,
+                output,
+            });
+        }
+
+        if args.peek().map(String::as_str) == Some("solve") {
+            args.next();
+            let (positional, mut options) = parse_options(args, &["--max-states", "--config"])?;
+            let (map, quest) = map_and_quest(positional)?;
+            let max_states = match options.remove("--max-states") {
+                Some(max_states) => max_states
+                    .parse()
+                    .map_err(|_| format!("--max-states requires a whole number\n{USAGE}"))?,
+                None => DEFAULT_MAX_STATES,
+            };
+            return Ok(Command::Solve {
+                map,
+                quest,
+                max_states,
+                config: options.remove("--config"),
+            });
+        }
+
+        Ok(Command::Play(Args::parse(args)?))
+    }
+}
+
+/// The command line arguments for playing the game
+pub struct Args {
+    /// The file name of the map
+    pub map: String,
+    /// The name of a built-in quest, or the file name of a quest
+    pub quest: String,
+    /// The file name of a save to load the game from
+    pub load: Option<String>,
+    /// The file name of a script of moves to play without a terminal
+    pub script: Option<String>,
+    /// The file name to record the session's key presses to
+    pub record: Option<String>,
+    /// The file name of a recorded session to replay
+    pub replay: Option<String>,
+    /// How many times faster than the original session a replay is played
+    pub speed: u32,
+    /// The file name to write a JSON Lines log of quest events to
+    pub jsonl: Option<String>,
+    /// The file name of the config file, instead of the default one
+    pub config: Option<String>,
+}
+
+impl Args {
+    /// Parse the command line arguments for playing the game
+    ///
+    /// # Errors
+    ///
+    /// If the map or quest is missing, an option is unknown or missing its value,
+    /// or options are given that can't be used together
+    fn parse(args: impl Iterator<Item = String>) -> Result<Args, Box<dyn Error>> {
+        let (positional, mut options) = parse_options(
+            args,
+            &[
+                "--load", "--script", "--record", "--replay", "--speed", "--jsonl", "--config",
+            ],
+        )?;
+        let (map, quest) = map_and_quest(positional)?;
+        let load = options.remove("--load");
+        let script = options.remove("--script");
+        let record = options.remove("--record");
+        let replay = options.remove("--replay");
+        let speed = options
+            .remove("--speed")
+            .map(|speed| {
+                speed
+                    .parse()
+                    .ok()
+                    .filter(|&speed| speed > 0)
+                    .ok_or(format!("--speed requires a positive whole number\n{USAGE}"))
+            })
+            .transpose()?;
+
+        if speed.is_some() && replay.is_none() {
+            return Err(format!("--speed can only be used with --replay\n{USAGE}").into());
+        }
+        // the recording starts from a fresh game, so it can't replay a loaded one
+        if record.is_some() && load.is_some() {
+            return Err(format!("--record can't be used with --load\n{USAGE}").into());
+        }
+        // a script is played from a fresh game without a terminal
+        if script.is_some() {
+            for (option, value) in [
+                ("--load", &load),
+                ("--record", &record),
+                ("--replay", &replay),
+            ] {
+                if value.is_some() {
+                    return Err(format!("--script can't be used with {option}\n{USAGE}").into());
+                }
+            }
+        }
+
+        Ok(Args {
+            map,
+            quest,
+            load,
+            script,
+            record,
+            replay,
+            speed: speed.unwrap_or(1),
+            jsonl: options.remove("--jsonl"),
+            config: options.remove("--config"),
+        })
+    }
+
+    /// The file name the game is saved to
+    ///
+    /// This is the file the game was loaded from, if any
+    pub fn save_file(&self) -> String {
+        self.load
+            .clone()
+            .unwrap_or_else(|| String::from(DEFAULT_SAVE_FILE))
+    }
+}
+
+/// Split the command line arguments into the positional arguments, in order,
+/// and the values of the given options, each of which takes a value
+///
+/// # Errors
+///
+/// If an option is not one of the given options, or is missing its value
+fn parse_options(
+    mut args: impl Iterator<Item = String>,
+    known: &[&'static str],
+) -> Result<(Vec<String>, HashMap<&'static str, String>), String> {
+    let mut positional = Vec::new();
+    let mut options = HashMap::new();
+
+    while let Some(arg) = args.next() {
+        if let Some(&option) = known.iter().find(|&&option| option == arg) {
+            options.insert(option, option_value(&mut args, option)?);
+        } else if arg.starts_with("--") {
+            return Err(format!("Unknown option {arg}\n{USAGE}"));
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    Ok((positional, options))
+}
+
+/// Take the map and quest from the positional command line arguments
+///
+/// # Errors
+///
+/// If the map or quest is missing, or there are more positional arguments
+fn map_and_quest(positional: Vec<String>) -> Result<(String, String), String> {
+    let mut positional = positional.into_iter();
+    let map = positional
+        .next()
+        .ok_or(format!("You failed to provide a map filename\n{USAGE}"))?;
+    let quest = positional.next().ok_or(format!(
+        "You failed to provide a quest (q1, q2, q3 or a quest filename)\n{USAGE}"
+    ))?;
+    no_more(positional)?;
+    Ok((map, quest))
+}
+
+/// Check that there are no command line arguments left over
+///
+/// # Errors
+///
+/// With the first argument left over
+fn no_more(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    match args.next() {
+        Some(arg) => Err(format!("Unexpected argument {arg}\n{USAGE}")),
+        None => Ok(()),
+    }
+}
+
+/// Take the value of an option from the remaining command line arguments
+fn option_value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or(format!("{option} requires a value\n{USAGE}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse the arguments for playing the game, given as one string
+    fn parse(args: &str) -> Result<Args, String> {
+        Args::parse(args.split_whitespace().map(String::from)).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn parse_test() {
+        let args = parse("map.ron q1 --replay keys.ron --speed 4 --config my.ron").unwrap();
+        assert_eq!((&*args.map, &*args.quest), ("map.ron", "q1"));
+        assert_eq!(args.replay.as_deref(), Some("keys.ron"));
+        assert_eq!(args.speed, 4);
+        assert_eq!(args.config.as_deref(), Some("my.ron"));
+
+        let first_line = |args| {
+            parse(args)
+                .err()
+                .unwrap()
+                .lines()
+                .next()
+                .unwrap()
+                .to_owned()
+        };
+        assert_eq!(first_line("map.ron q1 q2"), "Unexpected argument q2");
+        assert_eq!(first_line("map.ron q1 --fast"), "Unknown option --fast");
+        assert_eq!(first_line("map.ron q1 --load"), "--load requires a value");
+        assert_eq!(
+            first_line("map.ron q1 --script moves.txt --replay keys.ron"),
+            "--script can't be used with --replay"
+        );
+        assert_eq!(
+            first_line("map.ron --speed 0"),
+            "You failed to provide a quest (q1, q2, q3 or a quest filename)"
+        );
+    }
+}
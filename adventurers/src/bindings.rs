@@ -0,0 +1,216 @@
+//! # Key bindings module
+//!
+//! The keys that trigger each action in the game. Bindings start from a preset,
+//! and the keys of any action can then be replaced in the config file:
+//!
+//! ```text
+//! keys: (
+//!     preset: Vi,
+//!     bindings: {
+//!         ShowQuest: [Char('Q')],
+//!         Quit: [Esc, Ctrl('c')],
+//!     },
+//! )
+//! ```
+//!
+//! A key can only trigger one action, so conflicting bindings are reported
+//! before the game starts. Ctrl-C always quits the game, so it can't be bound
+//! to any other action
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::key::Key;
+
+/// The key that always quits the game, which is the game's quit event
+pub const QUIT_KEY: Key = Key::Ctrl('c');
+
+/// Something the player can do by pressing a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveNorth,
+    MoveEast,
+    MoveSouth,
+    MoveWest,
+    ShowQuest,
+    ShowInventory,
+    ShowHint,
+    ResetQuest,
+    Save,
+    Load,
+    Quit,
+}
+
+/// A set of bindings to start from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum Preset {
+    /// Move with the arrow keys
+    #[default]
+    Arrows,
+    /// Move with W, A, S and D, or the arrow keys
+    Wasd,
+    /// Move with H, J, K and L like in vi, or the arrow keys
+    Vi,
+}
+
+/// The key bindings as written in the config file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    /// The bindings to start from
+    pub preset: Preset,
+    /// The keys of each action that replace those of the preset
+    pub bindings: BTreeMap<Action, Vec<Key>>,
+}
+
+/// The action triggered by each key
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    /// The keys of each action
+    keys: BTreeMap<Action, Vec<Key>>,
+    /// The action triggered by each key
+    actions: HashMap<Key, Action>,
+}
+
+impl KeyBindings {
+    /// Create the key bindings given by the config
+    ///
+    /// # Errors
+    ///
+    /// If any key is bound to more than one action, or [`QUIT_KEY`] is bound
+    /// to an action other than quitting, listing every conflict
+    pub fn new(config: &KeysConfig) -> Result<Self, String> {
+        let mut keys = preset(config.preset);
+        keys.extend(config.bindings.clone());
+
+        let mut actions = HashMap::new();
+        let mut conflicts = Vec::new();
+        for (&action, action_keys) in &keys {
+            for &key in action_keys {
+                if key == QUIT_KEY && action != Action::Quit {
+                    conflicts.push(format!(
+                        "{key} always quits the game, so it can't be bound to {action:?}"
+                    ));
+                }
+                match actions.insert(key, action) {
+                    Some(other) if other != action => {
+                        conflicts.push(format!("{key} is bound to both {other:?} and {action:?}"))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(format!(
+                "Conflicting key bindings:\n{}",
+                conflicts.join("\n")
+            ));
+        }
+        Ok(Self { keys, actions })
+    }
+
+    /// The action triggered by the key, if any
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.actions.get(&key).copied()
+    }
+
+    /// The keys that trigger the action, e.g. to tell the player about them
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// A description of the first key that triggers the action, or a note that
+    /// no key does
+    pub fn describe(&self, action: Action) -> String {
+        match self.keys(action).first() {
+            Some(key) => key.to_string(),
+            None => String::from("(unbound)"),
+        }
+    }
+}
+
+/// The keys of each action in a preset
+fn preset(preset: Preset) -> BTreeMap<Action, Vec<Key>> {
+    let moves = match preset {
+        Preset::Arrows => [vec![], vec![], vec![], vec![]],
+        Preset::Wasd => [
+            vec![Key::Char('w')],
+            vec![Key::Char('d')],
+            vec![Key::Char('s')],
+            vec![Key::Char('a')],
+        ],
+        Preset::Vi => [
+            vec![Key::Char('k')],
+            vec![Key::Char('l')],
+            vec![Key::Char('j')],
+            vec![Key::Char('h')],
+        ],
+    };
+    let [north, east, south, west] = moves;
+
+    BTreeMap::from([
+        (Action::MoveNorth, [north, vec![Key::Up]].concat()),
+        (Action::MoveEast, [east, vec![Key::Right]].concat()),
+        (Action::MoveSouth, [south, vec![Key::Down]].concat()),
+        (Action::MoveWest, [west, vec![Key::Left]].concat()),
+        (Action::ShowQuest, vec![Key::Char('q')]),
+        (Action::ShowInventory, vec![Key::Char('i')]),
+        (Action::ShowHint, vec![Key::Char('?')]),
+        (Action::ResetQuest, vec![Key::Char('r')]),
+        (Action::Save, vec![Key::Ctrl('s')]),
+        (Action::Load, vec![Key::Ctrl('l')]),
+        (Action::Quit, vec![Key::Ctrl('c')]),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_test() {
+        for preset in [Preset::Arrows, Preset::Wasd, Preset::Vi] {
+            let bindings = KeyBindings::new(&KeysConfig {
+                preset,
+                bindings: BTreeMap::new(),
+            })
+            .unwrap();
+            assert_eq!(bindings.action(Key::Up), Some(Action::MoveNorth));
+            assert_eq!(bindings.action(Key::Ctrl('s')), Some(Action::Save));
+        }
+
+        let bindings = KeyBindings::new(&ron::from_str("(preset: Vi)").unwrap()).unwrap();
+        assert_eq!(bindings.action(Key::Char('h')), Some(Action::MoveWest));
+        assert_eq!(bindings.action(Key::Char('a')), None);
+    }
+
+    #[test]
+    fn conflict_test() {
+        let config: KeysConfig =
+            ron::from_str("(preset: Wasd, bindings: {Save: [Char('s')], Quit: [Esc]})").unwrap();
+        assert_eq!(
+            KeyBindings::new(&config).unwrap_err(),
+            "Conflicting key bindings:\n's' is bound to both MoveSouth and Save"
+        );
+
+        // replacing the keys of an action frees up the old ones
+        let config: KeysConfig =
+            ron::from_str("(preset: Wasd, bindings: {Save: [Char('s')], MoveSouth: [Char('x')]})")
+                .unwrap();
+        let bindings = KeyBindings::new(&config).unwrap();
+        assert_eq!(bindings.action(Key::Char('s')), Some(Action::Save));
+        assert_eq!(bindings.keys(Action::MoveSouth), &[Key::Char('x')]);
+
+        // the quit key is reserved, even once quitting has other keys
+        let config: KeysConfig =
+            ron::from_str("(bindings: {ShowHint: [Ctrl('c')], Quit: [Esc]})").unwrap();
+        assert_eq!(
+            KeyBindings::new(&config).unwrap_err(),
+            format!(
+                "Conflicting key bindings:\n{QUIT_KEY} always quits the game, so it can't be bound to ShowHint"
+            )
+        );
+    }
+}
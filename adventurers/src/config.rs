@@ -1,7 +1,296 @@
-/// The size of the viewport in rows and column
-pub const VP_SIZE: (i32, i32) = (78, 22);
-/// The number of spaces from the edge of the viewport that the player
-/// is allowed to walk on before the viewport moves to follow them
-pub const VP_BUFFER: i32 = 1;
-/// The default player health
-pub const PLAYER_HEALTH: i32 = 10;
+//! # Config module
+//!
+//! The game's constants, and the settings read from the config file. The config
+//! file is given with `--config`, or else read from
+//! `~/.config/adventurers/config.ron` if it exists (or from `$XDG_CONFIG_HOME`).
+//! Every setting is optional:
+//!
+//! ```text
+//! (
+//!     keys: (preset: Wasd),
+//!     viewport: (size: (60, 20), buffer: 3),
+//!     player: (spawn: (0, 0), health: 5, icon: '@'),
+//! )
+//! ```
+//!
+//! The player settings can also be given in the header of a map file, which
+//! overrides the config file (see [`crate::map_file`])
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{env, fs};
+
+use serde::{Deserialize, Serialize};
+use termgame::{Game, ViewportLocation};
+
+use crate::bindings::KeysConfig;
+use crate::movement::Coordinate;
+use crate::serde_util::{some, unwrap_some};
+
+/// The number of rows at the top of the viewport reserved for the HUD, which
+/// the player never walks into
+pub const HUD_ROWS: i32 = 1;
+/// The time between game ticks, when the game is played at normal speed
+pub const TICK_DURATION: Duration = Duration::from_millis(50);
+
+/// The settings read from the config file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ConfigFile {
+    /// The key bindings, see [`crate::bindings`]
+    pub keys: KeysConfig,
+    /// The size of the viewport, and how it follows the player
+    pub viewport: ViewportConfig,
+    /// The player's starting settings
+    pub player: PlayerOverrides,
+}
+
+impl ConfigFile {
+    /// Read the given config file, or else the default config file if it exists
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or is not a correctly formatted RON file with
+    /// a [`ConfigFile`] value
+    ///
+    /// # Returns
+    ///
+    /// The settings, which are all default if there is no config file
+    pub fn load(path: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config file {}: {e}", path.display()))?;
+        Ok(ron::from_str(&contents)
+            .map_err(|e| format!("Failed to read config file {} as RON: {e}", path.display()))?)
+    }
+
+    /// The player's starting settings, before any map overrides them
+    pub fn player(&self) -> PlayerConfig {
+        PlayerConfig::default().with(&self.player)
+    }
+}
+
+/// The file name of the default config file, if there is a config directory
+fn default_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("adventurers").join("config.ron"))
+}
+
+/// The viewport settings as written in the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ViewportConfig {
+    /// The number of columns and rows of the viewport, or `None` to fill the
+    /// game's screen
+    #[serde(deserialize_with = "some")]
+    pub size: Option<(i32, i32)>,
+    /// The number of spaces from the edge of the viewport that the player is
+    /// allowed to walk on before the viewport moves to follow them
+    pub buffer: i32,
+}
+
+impl Default for ViewportConfig {
+    fn default() -> Self {
+        Self {
+            size: None,
+            buffer: 1,
+        }
+    }
+}
+
+impl ViewportConfig {
+    /// Find the size of the viewport, given the screen size reported by
+    /// [`termgame::Game::screen_size`]
+    ///
+    /// The viewport fills the game's screen inside its border, unless a size is
+    /// given. It is never larger than that, since nothing more would be shown
+    pub fn resolve(&self, screen_size: (u16, (u16, u16))) -> Viewport {
+        let (width, (height, _)) = screen_size;
+        let screen = (i32::from(width) - 2, i32::from(height) - 2);
+        let (columns, rows) = self.size.unwrap_or(screen);
+        Viewport {
+            size: (columns.clamp(1, screen.0), rows.clamp(1, screen.1)),
+            buffer: self.buffer.max(0),
+        }
+    }
+}
+
+/// The size of the viewport, and how it follows the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    /// The number of columns and rows of the viewport
+    pub size: (i32, i32),
+    /// The number of spaces from the edge of the viewport that the player is
+    /// allowed to walk on before the viewport moves to follow them
+    pub buffer: i32,
+}
+
+impl Viewport {
+    /// Move the game's viewport to follow a position, e.g. the player's
+    ///
+    /// The viewport is centred on the position if it is out of view (or under
+    /// the HUD), e.g. after loading a saved game, and otherwise moves one space
+    /// when the position is close to the edge of the screen
+    pub fn follow(&self, game: &mut Game, position: Coordinate) {
+        let vp = game.get_viewport();
+        let visible_x = (vp.x..vp.x + self.size.0).contains(&position.x);
+        let visible_y = (vp.y + HUD_ROWS..vp.y + self.size.1).contains(&position.y);
+        if !visible_x || !visible_y {
+            game.set_viewport(ViewportLocation {
+                x: position.x - self.size.0 / 2,
+                y: position.y - self.size.1 / 2,
+            });
+        }
+
+        let vp = game.get_viewport();
+        let mut vp_x = vp.x;
+        let mut vp_y = vp.y;
+
+        if position.x - vp.x == self.buffer {
+            vp_x -= 1;
+        } else if vp.x + self.size.0 - 1 - position.x == self.buffer {
+            vp_x += 1;
+        }
+
+        if position.y - (vp.y + HUD_ROWS) == self.buffer {
+            vp_y -= 1;
+        } else if vp.y + self.size.1 - 1 - position.y == self.buffer {
+            vp_y += 1;
+        }
+
+        game.set_viewport(ViewportLocation { x: vp_x, y: vp_y });
+    }
+}
+
+/// The player's starting settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerConfig {
+    /// The position the player starts the game at
+    pub spawn: Coordinate,
+    /// The player's full health, which they start with
+    pub health: i32,
+    /// The icon to render the player as
+    pub icon: char,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            spawn: Coordinate::new(2, 2),
+            health: 10,
+            icon: '♟',
+        }
+    }
+}
+
+impl PlayerConfig {
+    /// Replace the settings given by the overrides
+    pub fn with(self, overrides: &PlayerOverrides) -> Self {
+        Self {
+            spawn: overrides
+                .spawn
+                .map_or(self.spawn, |(x, y)| Coordinate::new(x, y)),
+            health: overrides.health.unwrap_or(self.health),
+            icon: overrides.icon.unwrap_or(self.icon),
+        }
+    }
+
+    /// Check that the game can be played with these settings
+    ///
+    /// # Errors
+    ///
+    /// If the player's health is not positive, so they would start the game dead
+    pub fn check(&self) -> Result<(), String> {
+        if self.health <= 0 {
+            return Err(format!(
+                "The player's health must be positive, but is {}",
+                self.health
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Player settings that override the defaults, as written in the config file
+/// or a map header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerOverrides {
+    /// The position the player starts the game at
+    #[serde(
+        deserialize_with = "some",
+        serialize_with = "unwrap_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub spawn: Option<(i32, i32)>,
+    /// The player's full health, which they start with
+    #[serde(
+        deserialize_with = "some",
+        serialize_with = "unwrap_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub health: Option<i32>,
+    /// The icon to render the player as
+    #[serde(
+        deserialize_with = "some",
+        serialize_with = "unwrap_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub icon: Option<char>,
+}
+
+impl PlayerOverrides {
+    /// Whether no setting is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file_test() {
+        let config: ConfigFile =
+            ron::from_str("(viewport: (size: (40, 100)), player: (health: 5, icon: '@'))").unwrap();
+        assert_eq!(
+            config.viewport.resolve((80, (24, 0))),
+            Viewport {
+                size: (40, 22),
+                buffer: 1
+            }
+        );
+        assert_eq!(
+            ViewportConfig::default().resolve((80, (24, 0))).size,
+            (78, 22)
+        );
+
+        // the map header overrides the config file
+        let map_header = PlayerOverrides {
+            spawn: Some((0, -1)),
+            health: Some(3),
+            icon: None,
+        };
+        assert_eq!(
+            PlayerConfig::default()
+                .with(&config.player)
+                .with(&map_header),
+            PlayerConfig {
+                spawn: Coordinate::new(0, -1),
+                health: 3,
+                icon: '@',
+            }
+        );
+    }
+}
@@ -0,0 +1,583 @@
+//! # Editor module
+//!
+//! An in-terminal editor for map files, run with `adventurers edit <map file>`.
+//! The map is rendered as it is in the game, with a cursor to paint blocks onto
+//! it with a brush chosen from the palette:
+//!
+//! | Key            | Action                                                |
+//! |----------------|-------------------------------------------------------|
+//! | `0` to `9`     | Choose a brush from the palette                       |
+//! | Space or Enter | Paint the brush at the cursor                         |
+//! | Backspace      | Erase the block at the cursor                         |
+//! | `p`            | Pick up the block at the cursor as the brush          |
+//! | `m`            | Mark a corner of a rectangle at the cursor            |
+//! | `f`            | Fill the rectangle from the mark to the cursor        |
+//! | `u` and `y`    | Undo and redo                                         |
+//!
+//! The cursor is moved, the map saved, and the editor quit with the same keys
+//! as in the game (see [`crate::bindings`]), and the game's hint key shows this
+//! help. Choosing the object or sign brush asks for the object's character or
+//! the sign's text
+//!
+//! The map is saved in the format it was read in, with RON map files written
+//! in order of their coordinates so that changes are easy to review. A map
+//! file that doesn't exist yet is created on the first save
+
+use std::error::Error;
+use std::path::Path;
+
+use blocks::Block;
+use termgame::{run_game, Controller, Game, GameEvent, GameSettings, Message, StyledCharacter};
+
+use crate::bindings::{Action, KeyBindings};
+use crate::config::{ConfigFile, Viewport, ViewportConfig, TICK_DURATION};
+use crate::key::Key;
+use crate::map_file::MapFile;
+use crate::movement::{CardinalDirection, Coordinate};
+use crate::{hud, render_block};
+
+/// The blocks chosen with the number keys from `1`, after the eraser on `0`
+/// and before the object and sign brushes on `8` and `9`
+const PALETTE: [Block; 7] = [
+    Block::Grass,
+    Block::Sand,
+    Block::Rock,
+    Block::Cinderblock,
+    Block::Flowerbush,
+    Block::Barrier,
+    Block::Water,
+];
+
+/// The icon the cursor is rendered as
+const CURSOR_ICON: char = '┼';
+
+/// The icon the mark is rendered as
+const MARK_ICON: char = '╳';
+
+/// A change to the block at one position, which can be undone
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change {
+    position: Coordinate,
+    before: Option<Block>,
+    after: Option<Block>,
+}
+
+/// The state of the map being edited
+#[derive(Debug)]
+pub struct Editor {
+    /// The map file being edited
+    pub map_file: MapFile,
+    /// The position blocks are painted at
+    pub cursor: Coordinate,
+    /// The block that is painted, or `None` to erase blocks
+    pub brush: Option<Block>,
+    /// The corner of the rectangle to fill, opposite the cursor
+    pub mark: Option<Coordinate>,
+    /// The edits that can be undone, with the latest last
+    undo: Vec<Vec<Change>>,
+    /// The edits that can be redone, with the latest undone last
+    redo: Vec<Vec<Change>>,
+    /// Whether the map has changed since it was last saved
+    pub unsaved: bool,
+}
+
+impl Editor {
+    /// Create a new editor for the map file, with the cursor at the given position
+    pub fn new(map_file: MapFile, cursor: Coordinate) -> Self {
+        Self {
+            map_file,
+            cursor,
+            brush: Some(Block::Grass),
+            mark: None,
+            undo: vec![],
+            redo: vec![],
+            unsaved: false,
+        }
+    }
+
+    /// Move the cursor one space
+    pub fn move_cursor(&mut self, card_dir: CardinalDirection) {
+        self.cursor += card_dir;
+    }
+
+    /// Paint the brush at the cursor
+    ///
+    /// # Returns
+    ///
+    /// The positions whose block changed
+    pub fn paint(&mut self) -> Vec<Coordinate> {
+        self.edit(vec![self.cursor], self.brush.clone())
+    }
+
+    /// Erase the block at the cursor
+    ///
+    /// # Returns
+    ///
+    /// The positions whose block changed
+    pub fn erase(&mut self) -> Vec<Coordinate> {
+        self.edit(vec![self.cursor], None)
+    }
+
+    /// Fill the rectangle from the mark to the cursor with the brush, and remove
+    /// the mark. Without a mark, only the cursor is painted
+    ///
+    /// # Returns
+    ///
+    /// The positions whose block changed
+    pub fn fill(&mut self) -> Vec<Coordinate> {
+        let corner = self.mark.take().unwrap_or(self.cursor);
+        let (min_x, max_x) = (corner.x.min(self.cursor.x), corner.x.max(self.cursor.x));
+        let (min_y, max_y) = (corner.y.min(self.cursor.y), corner.y.max(self.cursor.y));
+        let positions = (min_y..=max_y)
+            .flat_map(|y| (min_x..=max_x).map(move |x| Coordinate::new(x, y)))
+            .collect();
+        self.edit(positions, self.brush.clone())
+    }
+
+    /// Use the block at the cursor as the brush, or the eraser if there is none
+    pub fn pick(&mut self) {
+        self.brush = self.map_file.map.get(&self.cursor.into()).cloned();
+    }
+
+    /// Undo the latest edit that hasn't been undone
+    ///
+    /// # Returns
+    ///
+    /// The positions whose block changed
+    pub fn undo(&mut self) -> Vec<Coordinate> {
+        let Some(changes) = self.undo.pop() else {
+            return vec![];
+        };
+        for change in changes.iter().rev() {
+            self.set(change.position, change.before.clone());
+        }
+        self.unsaved = true;
+        let positions = changes.iter().map(|change| change.position).collect();
+        self.redo.push(changes);
+        positions
+    }
+
+    /// Redo the latest edit that was undone
+    ///
+    /// # Returns
+    ///
+    /// The positions whose block changed
+    pub fn redo(&mut self) -> Vec<Coordinate> {
+        let Some(changes) = self.redo.pop() else {
+            return vec![];
+        };
+        for change in &changes {
+            self.set(change.position, change.after.clone());
+        }
+        self.unsaved = true;
+        let positions = changes.iter().map(|change| change.position).collect();
+        self.undo.push(changes);
+        positions
+    }
+
+    /// Save the map file, in the format given by its extension
+    ///
+    /// # Errors
+    ///
+    /// If the map file cannot be written
+    pub fn save(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        self.map_file.write(path)?;
+        self.unsaved = false;
+        Ok(())
+    }
+
+    /// The status line for the editor, cut short to the given width
+    pub fn status_line(&self, width: usize) -> String {
+        let mut parts = vec![
+            format!("({}, {})", self.cursor.x, self.cursor.y),
+            format!("Brush: {}", describe_brush(&self.brush)),
+        ];
+        parts.extend(
+            self.mark
+                .map(|mark| format!("Mark ({}, {})", mark.x, mark.y)),
+        );
+        parts.push(format!("{} blocks", self.map_file.map.len()));
+        parts.extend(self.map_file.title.clone());
+        if self.unsaved {
+            parts.push(String::from("Unsaved"));
+        }
+        hud::join(&parts, width)
+    }
+
+    /// Change the blocks at the positions, as one edit that can be undone
+    fn edit(&mut self, positions: Vec<Coordinate>, block: Option<Block>) -> Vec<Coordinate> {
+        let changes: Vec<Change> = positions
+            .into_iter()
+            .filter_map(|position| {
+                let before = self.map_file.map.get(&position.into()).cloned();
+                (before != block).then(|| Change {
+                    position,
+                    before,
+                    after: block.clone(),
+                })
+            })
+            .collect();
+        if changes.is_empty() {
+            return vec![];
+        }
+
+        for change in &changes {
+            self.set(change.position, change.after.clone());
+        }
+        self.unsaved = true;
+        self.redo.clear();
+        let positions = changes.iter().map(|change| change.position).collect();
+        self.undo.push(changes);
+        positions
+    }
+
+    /// Set or remove the block at a position
+    fn set(&mut self, position: Coordinate, block: Option<Block>) {
+        match block {
+            Some(block) => self.map_file.map.insert(position.into(), block),
+            None => self.map_file.map.remove(&position.into()),
+        };
+    }
+}
+
+/// The name of a brush, as shown in the status line and help
+fn describe_brush(brush: &Option<Block>) -> String {
+    match brush {
+        Some(block) => block.to_string(),
+        None => String::from("Eraser"),
+    }
+}
+
+/// What the editor is asking the user to type
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Prompt {
+    /// The character of an object brush
+    Object,
+    /// The text of a sign brush, typed so far
+    Sign(String),
+}
+
+/// The map editor, run in the terminal
+struct MapEditor {
+    /// The state of the map being edited
+    editor: Editor,
+    /// The file name the map is saved to
+    path: String,
+    /// The game's key bindings, for moving, saving and quitting
+    keys: KeyBindings,
+    /// The viewport settings from the config file
+    viewport_config: ViewportConfig,
+    /// The size of the viewport, and how it follows the cursor
+    viewport: Viewport,
+    /// The positions the status line was last rendered at
+    hud: Vec<Coordinate>,
+    /// What the user is being asked to type, if anything
+    prompt: Option<Prompt>,
+    /// Whether the user has been warned that quitting loses unsaved changes
+    quitting: bool,
+}
+
+impl MapEditor {
+    /// Render the cursor and the mark over the map, moving the viewport to
+    /// follow the cursor
+    fn render_cursor(&self, game: &mut Game) {
+        let mark = self.editor.mark.map(|mark| (mark, MARK_ICON));
+        for (position, icon) in mark.into_iter().chain([(self.editor.cursor, CURSOR_ICON)]) {
+            let sc = game.get_screen_char(position.x, position.y);
+            game.set_screen_char(
+                position.x,
+                position.y,
+                Some(sc.map_or(StyledCharacter::new(icon), |sc| sc.character(icon))),
+            );
+        }
+        self.viewport.follow(game, self.editor.cursor);
+    }
+
+    /// Render the changed positions, the cursor and the status line after
+    /// handling a key press
+    fn render(&mut self, game: &mut Game, changed: Vec<Coordinate>) {
+        let map = &self.editor.map_file.map;
+        for position in std::mem::take(&mut self.hud).into_iter().chain(changed) {
+            render_block(game, map, position);
+        }
+        self.render_cursor(game);
+
+        let width = self.viewport.size.0;
+        let line = self.editor.status_line(width as usize);
+        self.hud = hud::render(game, &line, width);
+    }
+
+    /// Show the keys of the editor
+    fn show_help(&self, game: &mut Game) {
+        let mut palette = vec![format!("0: {}", describe_brush(&None))];
+        palette.extend(
+            PALETTE
+                .iter()
+                .enumerate()
+                .map(|(i, block)| format!("{}: {block}", i + 1)),
+        );
+        palette.extend([String::from("8: Object"), String::from("9: Sign")]);
+
+        let help = format!(
+            "Brushes: {}\n\
+             Space or Enter: paint, Backspace: erase, p: pick up the block\n\
+             m: mark a corner, f: fill from the mark to the cursor\n\
+             u: undo, y: redo, {}: save, {}: quit",
+            palette.join(", "),
+            self.keys.describe(Action::Save),
+            self.keys.describe(Action::Quit),
+        );
+        game.set_message(Some(Message::new(help).title(String::from("Map editor"))));
+    }
+
+    /// Handle a key press while the user is being asked to type something
+    fn handle_prompt(&mut self, game: &mut Game, prompt: Prompt, key: Key) {
+        self.prompt = match (prompt, key) {
+            (_, Key::Esc) => None,
+            (Prompt::Object, Key::Char(c)) => {
+                self.editor.brush = Some(Block::Object(c));
+                None
+            }
+            (Prompt::Sign(text), Key::Enter) => {
+                self.editor.brush = Some(Block::Sign(text));
+                None
+            }
+            (Prompt::Sign(mut text), Key::Char(c)) => {
+                text.push(c);
+                Some(Prompt::Sign(text))
+            }
+            (Prompt::Sign(mut text), Key::Backspace) => {
+                text.pop();
+                Some(Prompt::Sign(text))
+            }
+            (prompt, _) => Some(prompt),
+        };
+
+        self.show_prompt(game);
+    }
+
+    /// Show what the user is being asked to type, if anything
+    fn show_prompt(&self, game: &mut Game) {
+        let message = match &self.prompt {
+            Some(Prompt::Object) => Message::new(String::from(
+                "Type the object's character, or press Esc to cancel",
+            ))
+            .title(String::from("Object")),
+            Some(Prompt::Sign(text)) => Message::new(format!(
+                "{text}_\n\nType the sign's text and press Enter, or press Esc to cancel"
+            ))
+            .title(String::from("Sign")),
+            None => {
+                game.set_message(None);
+                return;
+            }
+        };
+        game.set_message(Some(message));
+    }
+
+    /// Handle a key press, returning the positions whose block changed
+    fn handle_key(&mut self, game: &mut Game, key: Key) -> Vec<Coordinate> {
+        if let Some(prompt) = self.prompt.take() {
+            self.handle_prompt(game, prompt, key);
+            return vec![];
+        }
+
+        let quitting = std::mem::take(&mut self.quitting);
+        game.set_message(None);
+
+        let old_cursor = self.editor.cursor;
+        let old_mark = self.editor.mark;
+        let mut changed = match (self.keys.action(key), key) {
+            (Some(Action::MoveNorth), _) => self.move_cursor(CardinalDirection::North),
+            (Some(Action::MoveEast), _) => self.move_cursor(CardinalDirection::East),
+            (Some(Action::MoveSouth), _) => self.move_cursor(CardinalDirection::South),
+            (Some(Action::MoveWest), _) => self.move_cursor(CardinalDirection::West),
+            (Some(Action::ShowHint), _) => {
+                self.show_help(game);
+                vec![]
+            }
+            (Some(Action::Save), _) => {
+                let message = match self.editor.save(&self.path) {
+                    Ok(()) => format!("Map saved to {}", self.path),
+                    Err(e) => e.to_string(),
+                };
+                game.set_message(Some(Message::new(message).title(String::from("Save"))));
+                vec![]
+            }
+            (Some(Action::Quit), _) => {
+                if self.editor.unsaved && !quitting {
+                    self.quitting = true;
+                    game.set_message(Some(
+                        Message::new(format!(
+                            "The map has unsaved changes. Press {} again to quit without \
+                             saving, or {} to save",
+                            self.keys.describe(Action::Quit),
+                            self.keys.describe(Action::Save)
+                        ))
+                        .title(String::from("Quit")),
+                    ));
+                } else {
+                    game.end_game();
+                }
+                vec![]
+            }
+
+            (_, Key::Char('0')) => {
+                self.editor.brush = None;
+                vec![]
+            }
+            (_, Key::Char(c @ '1'..='7')) => {
+                self.editor.brush = Some(PALETTE[c as usize - '1' as usize].clone());
+                vec![]
+            }
+            (_, Key::Char('8')) => {
+                self.prompt = Some(Prompt::Object);
+                self.show_prompt(game);
+                vec![]
+            }
+            (_, Key::Char('9')) => {
+                self.prompt = Some(Prompt::Sign(String::new()));
+                self.show_prompt(game);
+                vec![]
+            }
+            (_, Key::Char(' ') | Key::Enter) => self.editor.paint(),
+            (_, Key::Backspace) => self.editor.erase(),
+            (_, Key::Char('p')) => {
+                self.editor.pick();
+                vec![]
+            }
+            (_, Key::Char('m')) => {
+                self.editor.mark = Some(self.editor.cursor);
+                vec![]
+            }
+            (_, Key::Char('f')) => self.editor.fill(),
+            (_, Key::Char('u')) => self.editor.undo(),
+            (_, Key::Char('y')) => self.editor.redo(),
+            _ => vec![],
+        };
+
+        // the cursor and mark are drawn over the map, so are cleared where they were
+        changed.push(old_cursor);
+        changed.extend(old_mark);
+        changed
+    }
+
+    /// Move the cursor, which changes no blocks
+    fn move_cursor(&mut self, card_dir: CardinalDirection) -> Vec<Coordinate> {
+        self.editor.move_cursor(card_dir);
+        vec![]
+    }
+}
+
+impl Controller for MapEditor {
+    fn on_start(&mut self, game: &mut Game) {
+        self.viewport = self.viewport_config.resolve(game.screen_size());
+        let map = &self.editor.map_file.map;
+        for &(x, y) in map.keys() {
+            render_block(game, map, Coordinate::new(x, y));
+        }
+        self.render(game, vec![]);
+        self.show_help(game);
+    }
+
+    fn on_event(&mut self, game: &mut Game, event: GameEvent) {
+        let Some(key) = Key::from_event(event) else {
+            return;
+        };
+        let changed = self.handle_key(game, key);
+        self.render(game, changed);
+    }
+
+    fn on_tick(&mut self, _game: &mut Game) {}
+}
+
+/// Edit a map file in the terminal, with the settings from the config file
+///
+/// # Errors
+///
+/// If the map file exists but cannot be read, or the key bindings conflict
+pub fn run(path: &str, config: &ConfigFile) -> Result<(), Box<dyn Error>> {
+    let map_file = if Path::new(path).exists() {
+        MapFile::read(path)?
+    } else {
+        MapFile::default()
+    };
+    let cursor = config.player().with(&map_file.player).spawn;
+
+    let mut editor = MapEditor {
+        editor: Editor::new(map_file, cursor),
+        path: String::from(path),
+        keys: KeyBindings::new(&config.keys)?,
+        viewport_config: config.viewport,
+        viewport: config
+            .viewport
+            .resolve((termgame::SCREEN_WIDTH, (termgame::SCREEN_HEIGHT, 0))),
+        hud: vec![],
+        prompt: None,
+        quitting: false,
+    };
+    // the quit key is handled by the editor, to warn about unsaved changes
+    run_game(
+        &mut editor,
+        GameSettings::new()
+            .tick_duration(TICK_DURATION)
+            .quit_event(None),
+    )?;
+
+    if editor.editor.unsaved {
+        println!("Quit without saving the changes to {path}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Map;
+
+    #[test]
+    fn edit_test() {
+        let map_file = MapFile {
+            map: Map::from([((0, 0), Block::Water)]),
+            ..MapFile::default()
+        };
+        let mut editor = Editor::new(map_file, Coordinate::new(0, 0));
+
+        editor.pick();
+        assert_eq!(editor.brush, Some(Block::Water));
+        // painting the same block changes nothing
+        assert_eq!(editor.paint(), vec![]);
+        assert!(!editor.unsaved);
+
+        editor.mark = Some(Coordinate::new(-1, 1));
+        editor.brush = Some(Block::Sand);
+        editor.move_cursor(CardinalDirection::East);
+        assert_eq!(editor.fill().len(), 6);
+        assert_eq!(editor.mark, None);
+        assert_eq!(editor.map_file.map.len(), 6);
+        assert!(editor.unsaved);
+
+        assert_eq!(editor.erase(), vec![Coordinate::new(1, 0)]);
+        assert_eq!(editor.map_file.map.len(), 5);
+
+        editor.undo();
+        assert_eq!(editor.map_file.map.len(), 6);
+        editor.undo();
+        assert_eq!(editor.map_file.map, Map::from([((0, 0), Block::Water)]));
+        // there is nothing left to undo
+        assert_eq!(editor.undo(), vec![]);
+
+        editor.redo();
+        assert_eq!(editor.map_file.map[&(0, 0)], Block::Sand);
+        // a new edit can't be redone over
+        editor.brush = None;
+        editor.paint();
+        assert_eq!(editor.redo(), vec![]);
+        assert_eq!(editor.map_file.map.len(), 5);
+
+        assert_eq!(
+            editor.status_line(78),
+            "(1, 0) │ Brush: Eraser │ 5 blocks │ Unsaved"
+        );
+    }
+}
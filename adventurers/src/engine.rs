@@ -0,0 +1,595 @@
+//! # Engine module
+//!
+//! The rules of the game, independent of how the game is displayed
+//!
+//! Each action taken in the game returns a list of [`Outcome`]s describing what
+//! happened, which can then be rendered (or ignored, e.g. in tests)
+
+use std::error::Error;
+
+use adventurers_quest::definition::QuestDef;
+use adventurers_quest::quests::Holdings;
+use adventurers_quest::{Event, Quest, QuestStatus};
+use blocks::Block;
+
+use crate::event_log::EventLog;
+use crate::map_file::Regions;
+use crate::movement::{shortest_path, CardinalDirection, Coordinate};
+use crate::player::Player;
+use crate::{map_bounds, Map};
+
+/// Something that happened as the result of an action in the game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player moved from one position to another
+    Moved { from: Coordinate, to: Coordinate },
+    /// The player was stopped from moving onto a block
+    Blocked { position: Coordinate, block: Block },
+    /// The player took damage, leaving them with the given health
+    Damaged { damage: i32, health: i32 },
+    /// A block was removed from the map, e.g. an object was picked up
+    Removed { position: Coordinate, block: Block },
+    /// A message should be shown to the player
+    Message(String),
+    /// The player completed the quest
+    Won,
+    /// The player failed the quest
+    Lost,
+    /// The player ran out of health
+    Died,
+}
+
+/// The state of the game and its rules
+pub struct Engine {
+    /// The map of the game
+    pub map: Map,
+    /// The named regions of the map
+    pub regions: Regions,
+    /// The player of the game
+    pub player: Player,
+    /// The quest being completed
+    pub quest: Box<dyn Quest<Event>>,
+    /// The log that registered events are written to, if any
+    pub event_log: Option<EventLog>,
+    /// The map and player as they were at the start of the game
+    start: (Map, Player),
+    /// The player's inventory, shared with the quest's collect quests
+    holdings: Holdings,
+}
+
+impl Engine {
+    /// Create a new engine given the map, player and the definition of the quest
+    ///
+    /// The quest's collect quests count the objects in the player's inventory,
+    /// including those held before they started
+    pub fn new(map: Map, player: Player, quest: &QuestDef) -> Self {
+        let holdings = Holdings::default();
+        holdings.set(player.inventory().items());
+        Self {
+            start: (map.clone(), player.clone()),
+            map,
+            regions: Regions::new(),
+            player,
+            quest: quest.build_holding(&holdings),
+            event_log: None,
+            holdings,
+        }
+    }
+
+    /// Try to move the player one block in the given direction
+    ///
+    /// Players that have run out of health can no longer move
+    pub fn do_move(&mut self, card_dir: CardinalDirection) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+
+        if self.player.is_dead() {
+            return outcomes;
+        }
+
+        let old_pos = self.player.position();
+        let new_pos = old_pos + card_dir;
+        let destination_block = self.map.get(&new_pos.into()).cloned();
+
+        // check if destination block can be walked on
+        if let Some(block) = destination_block.clone().filter(Block::blocks_movement) {
+            outcomes.push(Outcome::Blocked {
+                position: new_pos,
+                block: block.clone(),
+            });
+            self.register_event(&Event::Blocked(block), &mut outcomes);
+            return outcomes;
+        }
+
+        self.player.move_to(new_pos);
+        outcomes.push(Outcome::Moved {
+            from: old_pos,
+            to: new_pos,
+        });
+
+        // check if destination block should prompt a message
+        let message = destination_block.as_ref().and_then(Block::message);
+        if let Some(message) = &message {
+            outcomes.push(Outcome::Message(message.clone()));
+        }
+
+        // check if destination block hurts the player
+        let damage = destination_block.as_ref().map_or(0, Block::damage);
+        if damage != 0 {
+            self.player.take_damage(damage);
+            outcomes.push(Outcome::Damaged {
+                damage,
+                health: self.player.health(),
+            });
+        } else {
+            // player restores health
+            self.player.restore_health();
+        }
+
+        // check if the player has just died
+        if self.player.is_dead() {
+            outcomes.push(Outcome::Died);
+        }
+
+        // register events
+        self.register_event(&Event::Move(destination_block), &mut outcomes);
+        if let Some(message) = message {
+            self.register_event(&Event::ReadSign(message), &mut outcomes);
+        }
+        if damage != 0 {
+            self.register_event(&Event::Damage(damage), &mut outcomes);
+        }
+        if self.player.is_dead() {
+            self.register_event(&Event::Drown, &mut outcomes);
+        }
+        self.register_event(&Event::Reach(new_pos.x, new_pos.y), &mut outcomes);
+        let regions: Vec<String> = self
+            .regions
+            .iter()
+            .filter(|(_, rect)| rect.contains(new_pos.into()))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in regions {
+            self.register_event(&Event::InRegion(name), &mut outcomes);
+        }
+
+        // objects disappear and are picked up once they have been walked on
+        if let Some(&Block::Object(item)) = self.map.get(&old_pos.into()) {
+            if let Some(block) = self.map.remove(&old_pos.into()) {
+                outcomes.push(Outcome::Removed {
+                    position: old_pos,
+                    block,
+                });
+            }
+            let held = self.player.pick_up(item);
+            self.register_event(&Event::Pickup { item, held }, &mut outcomes);
+        }
+
+        outcomes
+    }
+
+    /// Let a game tick pass
+    ///
+    /// Time stops for players that have run out of health
+    pub fn tick(&mut self) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+        if self.player.is_dead() {
+            return outcomes;
+        }
+
+        self.register_event(&Event::Tick, &mut outcomes);
+        outcomes
+    }
+
+    /// The events that would make progress on the quest, of those that happen
+    /// when the player moves somewhere: walking on a block, reaching a
+    /// coordinate or entering a named region
+    pub fn hint_targets(&self) -> Vec<Event> {
+        self.quest
+            .targets()
+            .into_iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    Event::Move(Some(_)) | Event::Reach(..) | Event::InRegion(_)
+                )
+            })
+            .collect()
+    }
+
+    /// Find the shortest route to the nearest position that makes progress on
+    /// the quest, using the player's current health
+    ///
+    /// # Returns
+    ///
+    /// The positions along the route, ending with the target position, and the
+    /// event that happens there, or `None` if the quest has no
+    /// [hint targets](Self::hint_targets) or none of them can be reached
+    pub fn route_to_target(&self) -> Option<(Vec<Coordinate>, Event)> {
+        let targets = self.hint_targets();
+        if targets.is_empty() {
+            return None;
+        }
+
+        // coordinates and regions to reach may lie beyond the blocks of the map
+        let start = self.player.position();
+        let (mut min, mut max) = map_bounds(&self.map, start);
+        for event in &targets {
+            let corners = match event {
+                Event::Reach(x, y) => vec![(*x, *y)],
+                Event::InRegion(name) => self
+                    .regions
+                    .get(name)
+                    .map_or(vec![], |rect| vec![rect.min, rect.max]),
+                _ => vec![],
+            };
+            for (x, y) in corners {
+                min = Coordinate::new(min.x.min(x), min.y.min(y));
+                max = Coordinate::new(max.x.max(x), max.y.max(y));
+            }
+        }
+
+        let route = shortest_path(
+            &self.map,
+            start,
+            self.player.health(),
+            self.player.max_health(),
+            (min, max),
+            |position| targets.iter().any(|event| self.happens_at(event, position)),
+        )?;
+        let destination = route[route.len() - 1];
+        let event = targets
+            .into_iter()
+            .find(|event| self.happens_at(event, destination))?;
+        Some((route, event))
+    }
+
+    /// Start the game again, so that the player can try again after failing
+    /// the quest
+    ///
+    /// The map and player are put back as they were when the engine was
+    /// created, and the quest is reset
+    pub fn restart(&mut self) {
+        let (map, player) = &self.start;
+        self.map = map.clone();
+        self.player = player.clone();
+        self.holdings.set(self.player.inventory().items());
+        self.quest.reset();
+    }
+
+    /// Write the summary of the session to the event log and close it, if there
+    /// is one
+    ///
+    /// # Errors
+    ///
+    /// If the event log could not be written
+    pub fn finish_event_log(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.event_log.take() {
+            Some(event_log) => event_log.finish(&self.player, self.quest.as_ref()),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether moving onto the position makes the event happen
+    fn happens_at(&self, event: &Event, position: Coordinate) -> bool {
+        match event {
+            Event::Move(Some(block)) => self.map.get(&position.into()) == Some(block),
+            Event::Reach(x, y) => position == Coordinate::new(*x, *y),
+            Event::InRegion(name) => self
+                .regions
+                .get(name)
+                .is_some_and(|rect| rect.contains(position.into())),
+            _ => false,
+        }
+    }
+
+    /// Register an event with the quest, adding an outcome if the quest has
+    /// just been completed or failed
+    fn register_event(&mut self, event: &Event, outcomes: &mut Vec<Outcome>) {
+        if self.player.won || self.player.failed {
+            return;
+        }
+
+        self.holdings.set(self.player.inventory().items());
+        let status = self.quest.register_event(event);
+        // ticks happen many times a second, so only those that end the quest are logged
+        if let Some(event_log) = &mut self.event_log {
+            if *event != Event::Tick || status != QuestStatus::Ongoing {
+                event_log.log_event(event, &self.player, status);
+            }
+        }
+
+        match status {
+            QuestStatus::Complete => {
+                self.player.won = true;
+                outcomes.push(Outcome::Won);
+            }
+            QuestStatus::Failed => {
+                self.player.failed = true;
+                outcomes.push(Outcome::Lost);
+            }
+            QuestStatus::Ongoing => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{engine, map};
+
+    #[test]
+    fn blocked_test() {
+        use Block::*;
+        let mut engine = engine(
+            map(&[
+                &[Barrier, Barrier, Barrier],
+                &[Barrier, Grass, Grass],
+                &[Barrier, Grass, Grass],
+            ]),
+            "Walk(Sand)",
+        );
+
+        assert_eq!(
+            engine.do_move(CardinalDirection::West),
+            vec![Outcome::Moved {
+                from: Coordinate::new(2, 2),
+                to: Coordinate::new(1, 2),
+            }]
+        );
+        assert_eq!(
+            engine.do_move(CardinalDirection::West),
+            vec![Outcome::Blocked {
+                position: Coordinate::new(0, 2),
+                block: Barrier,
+            }]
+        );
+        assert_eq!(engine.player.position(), Coordinate::new(1, 2));
+
+        // empty tiles can be walked on
+        engine.do_move(CardinalDirection::South);
+        assert_eq!(engine.player.position(), Coordinate::new(1, 3));
+    }
+
+    #[test]
+    fn drown_test() {
+        use Block::*;
+        let row = vec![Water; 20];
+        let mut engine = engine(map(&[&row, &row, &row]), "Walk(Sand)");
+
+        for _ in 1..engine.player.max_health() {
+            let outcomes = engine.do_move(CardinalDirection::East);
+            assert!(!outcomes.contains(&Outcome::Died));
+        }
+        let outcomes = engine.do_move(CardinalDirection::East);
+        assert!(outcomes.contains(&Outcome::Damaged {
+            damage: 1,
+            health: 0,
+        }));
+        assert!(outcomes.contains(&Outcome::Died));
+        assert!(engine.player.is_dead());
+
+        // dead players cannot move
+        assert_eq!(engine.do_move(CardinalDirection::East), vec![]);
+    }
+
+    #[test]
+    fn sign_and_object_test() {
+        use Block::*;
+        let mut engine = engine(
+            map(&[&[
+                Grass,
+                Grass,
+                Grass,
+                Object('x'),
+                Sign(String::from("Hello")),
+            ]]),
+            "Collect('x', 1)",
+        );
+        engine.player.move_to(Coordinate::new(2, 0));
+
+        engine.do_move(CardinalDirection::East);
+        assert_eq!(engine.map.get(&(3, 0)), Some(&Object('x')));
+
+        let outcomes = engine.do_move(CardinalDirection::East);
+        assert_eq!(
+            outcomes,
+            vec![
+                Outcome::Moved {
+                    from: Coordinate::new(3, 0),
+                    to: Coordinate::new(4, 0),
+                },
+                Outcome::Message(String::from("Hello")),
+                Outcome::Removed {
+                    position: Coordinate::new(3, 0),
+                    block: Object('x'),
+                },
+                Outcome::Won,
+            ]
+        );
+        assert_eq!(engine.map.get(&(3, 0)), None);
+        assert_eq!(engine.player.inventory().total(), 1);
+    }
+
+    /// Objects picked up before a collect quest starts count towards it
+    #[test]
+    fn collect_later_test() {
+        use Block::*;
+        let mut engine = engine(
+            map(&[&[Grass, Object('x'), Grass, Sand]]),
+            "Then(Walk(Sand), Collect('x', 1))",
+        );
+        engine.player.move_to(Coordinate::new(0, 0));
+
+        engine.do_move(CardinalDirection::East);
+        engine.do_move(CardinalDirection::East);
+        assert_eq!(engine.player.inventory().total(), 1);
+        assert!(!engine.player.won);
+
+        let outcomes = engine.do_move(CardinalDirection::East);
+        assert_eq!(outcomes.last(), Some(&Outcome::Won));
+    }
+
+    #[test]
+    fn route_to_target_test() {
+        use Block::*;
+        let mut engine = engine(
+            map(&[
+                &[Sand, Grass, Grass, Grass],
+                &[Barrier, Barrier, Grass, Barrier],
+                &[Grass, Grass, Grass, Object('x')],
+            ]),
+            "Then(Walk(Sand), Walk(Object('x')))",
+        );
+
+        assert_eq!(
+            engine.route_to_target(),
+            Some((
+                vec![
+                    Coordinate::new(2, 1),
+                    Coordinate::new(2, 0),
+                    Coordinate::new(1, 0),
+                    Coordinate::new(0, 0),
+                ],
+                Event::on_block(Sand)
+            ))
+        );
+
+        engine.player.move_to(Coordinate::new(1, 0));
+        engine.do_move(CardinalDirection::West);
+        assert_eq!(engine.route_to_target().unwrap().0.len(), 5);
+
+        engine.player.move_to(Coordinate::new(2, 2));
+        engine.do_move(CardinalDirection::East);
+        assert!(engine.player.won);
+        assert_eq!(engine.route_to_target(), None);
+    }
+
+    #[test]
+    fn lose_and_reset_test() {
+        use Block::*;
+        let mut engine = engine(map(&[&[Grass, Water, Grass, Sand]]), "Avoid(Water, 2)");
+        engine.player.move_to(Coordinate::new(2, 0));
+
+        assert_eq!(
+            engine.do_move(CardinalDirection::West),
+            vec![
+                Outcome::Moved {
+                    from: Coordinate::new(2, 0),
+                    to: Coordinate::new(1, 0),
+                },
+                Outcome::Damaged {
+                    damage: 1,
+                    health: engine.player.max_health() - 1,
+                },
+                Outcome::Lost,
+            ]
+        );
+        assert!(engine.player.failed);
+
+        // the player starts again from the spawn point at full health
+        engine.restart();
+        assert!(!engine.player.failed);
+        assert_eq!(engine.player.position(), Coordinate::new(2, 2));
+        assert_eq!(engine.player.health(), engine.player.max_health());
+        engine.player.move_to(Coordinate::new(1, 0));
+        engine.do_move(CardinalDirection::East);
+        assert_eq!(
+            engine.do_move(CardinalDirection::East),
+            vec![
+                Outcome::Moved {
+                    from: Coordinate::new(2, 0),
+                    to: Coordinate::new(3, 0),
+                },
+                Outcome::Won,
+            ]
+        );
+    }
+
+    /// Time limits in ticks run out from game ticks alone
+    #[test]
+    fn time_limit_test() {
+        use Block::*;
+        let mut engine = engine(map(&[&[Grass, Sand]]), "Within(Walk(Sand), 3, Ticks, Fail)");
+
+        assert_eq!(engine.tick(), vec![]);
+        assert_eq!(engine.tick(), vec![]);
+        assert_eq!(engine.tick(), vec![Outcome::Lost]);
+        assert!(engine.player.failed);
+    }
+
+    /// Time stops once the player has run out of health
+    #[test]
+    fn dead_time_limit_test() {
+        use Block::*;
+        let mut engine = engine(map(&[&[Grass, Sand]]), "Within(Walk(Sand), 1, Ticks, Fail)");
+
+        engine.player.take_damage(engine.player.health());
+        assert_eq!(engine.tick(), vec![]);
+        assert!(!engine.player.failed);
+    }
+
+    /// Named regions are entered by moving onto any coordinate within them
+    #[test]
+    fn region_test() {
+        use adventurers_quest::quests::Rect;
+        use Block::*;
+
+        let mut engine = engine(
+            map(&[&[Grass, Grass, Grass, Grass]]),
+            "Region(Named(\"east\"))",
+        );
+        engine.regions.insert(
+            String::from("east"),
+            Rect {
+                min: (4, 0),
+                max: (5, 3),
+            },
+        );
+        assert_eq!(
+            engine.do_move(CardinalDirection::East).last(),
+            Some(&Outcome::Moved {
+                from: Coordinate::new(2, 2),
+                to: Coordinate::new(3, 2),
+            })
+        );
+        assert_eq!(
+            engine.do_move(CardinalDirection::East).last(),
+            Some(&Outcome::Won)
+        );
+    }
+
+    /// Coordinates and regions to reach are hinted, even beyond the map's blocks
+    #[test]
+    fn route_to_position_test() {
+        use adventurers_quest::quests::Rect;
+        use Block::*;
+
+        let reach = engine(map(&[&[Grass, Barrier, Grass]]), "Reach(5, 2)");
+        let (route, target) = reach.route_to_target().unwrap();
+        assert_eq!(route.last(), Some(&Coordinate::new(5, 2)));
+        assert_eq!(route.len(), 3);
+        assert_eq!(target, Event::Reach(5, 2));
+
+        let rect = engine(map(&[&[Grass]]), "Region(Rect((min: (0, 4), max: (3, 5))))");
+        assert_eq!(
+            rect.route_to_target(),
+            Some((
+                vec![Coordinate::new(2, 3), Coordinate::new(2, 4)],
+                Event::Reach(2, 4)
+            ))
+        );
+
+        let mut named = engine(map(&[&[Grass]]), "Region(Named(\"cave\"))");
+        assert_eq!(named.route_to_target(), None);
+        named.regions.insert(
+            String::from("cave"),
+            Rect {
+                min: (-3, 2),
+                max: (-1, 2),
+            },
+        );
+        let (route, target) = named.route_to_target().unwrap();
+        assert_eq!(route.len(), 3);
+        assert_eq!(target, Event::InRegion(String::from("cave")));
+    }
+}
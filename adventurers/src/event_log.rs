@@ -0,0 +1,242 @@
+//! # Event log module
+//!
+//! Write every [`Event`] registered with the quest to a JSON Lines file, along
+//! with the state of the player at the time, followed by a summary of the session
+//!
+//! The records are described alongside [`Event`] in the `adventurers_quest` crate
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use adventurers_quest::progress::QuestProgress;
+use adventurers_quest::{Event, Quest, QuestStatus};
+use serde::Serialize;
+
+use crate::movement::Coordinate;
+use crate::player::Player;
+
+/// A line of the event log
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    /// An event registered with the quest
+    Event {
+        event: &'a Event,
+        position: Coordinate,
+        health: i32,
+        status: QuestStatus,
+    },
+    /// The state of the game at the end of the session
+    Summary {
+        events: usize,
+        position: Coordinate,
+        health: i32,
+        status: QuestStatus,
+        won: bool,
+        failed: bool,
+        dead: bool,
+        inventory: BTreeMap<char, u32>,
+        quest: QuestProgress,
+    },
+}
+
+/// A JSON Lines file that events are written to as they are registered
+pub struct EventLog {
+    /// Where the records are written
+    writer: Box<dyn Write>,
+    /// The number of events written so far
+    events: usize,
+    /// The first error writing a record, reported when the log is finished
+    error: Option<std::io::Error>,
+}
+
+impl EventLog {
+    /// Create a new event log writing to the given file
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be created
+    pub fn create(path: &str) -> Result<Self, Box<dyn Error>> {
+        let file =
+            File::create(path).map_err(|e| format!("Failed to create event log {path}: {e}"))?;
+        Ok(Self::new(Box::new(BufWriter::new(file))))
+    }
+
+    /// Create a new event log writing to the given writer
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer,
+            events: 0,
+            error: None,
+        }
+    }
+
+    /// Write an event that was registered with the quest, and the status it
+    /// resulted in
+    ///
+    /// Errors are kept until the log is finished, so that they don't interrupt
+    /// the game
+    pub fn log_event(&mut self, event: &Event, player: &Player, status: QuestStatus) {
+        self.events += 1;
+        self.write(&Record::Event {
+            event,
+            position: player.position(),
+            health: player.health(),
+            status,
+        });
+    }
+
+    /// Write the summary of the session, and flush the log
+    ///
+    /// # Errors
+    ///
+    /// If any record could not be written
+    pub fn finish(
+        mut self,
+        player: &Player,
+        quest: &dyn Quest<Event>,
+    ) -> Result<(), Box<dyn Error>> {
+        let progress = quest.progress();
+        self.write(&Record::Summary {
+            events: self.events,
+            position: player.position(),
+            health: player.health(),
+            status: progress.status,
+            won: player.won,
+            failed: player.failed,
+            dead: player.is_dead(),
+            inventory: player.inventory().items().clone(),
+            quest: progress,
+        });
+
+        if let Some(e) = self.error.take() {
+            return Err(format!("Failed to write event log: {e}").into());
+        }
+        self.writer
+            .flush()
+            .map_err(|e| format!("Failed to write event log: {e}"))?;
+        Ok(())
+    }
+
+    /// Write a record as a line of JSON, keeping the first error
+    fn write(&mut self, record: &Record) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let result = serde_json::to_writer(&mut self.writer, record)
+            .map_err(std::io::Error::from)
+            .and_then(|()| self.writer.write_all(b"\n"));
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use adventurers_quest::definition::QuestDef;
+    use blocks::Block;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::engine::Engine;
+    use crate::movement::CardinalDirection;
+    use crate::Map;
+
+    /// Events are written as documented alongside [`Event`]
+    #[test]
+    fn event_json_test() {
+        let to_json = |event: Event| serde_json::to_string(&event).unwrap();
+        assert_eq!(to_json(Event::on_block(Block::Sand)), r#"{"Move":"Sand"}"#);
+        assert_eq!(
+            to_json(Event::on_block(Block::Object('x'))),
+            r#"{"Move":{"Object":"x"}}"#
+        );
+        assert_eq!(to_json(Event::Move(None)), r#"{"Move":null}"#);
+        assert_eq!(to_json(Event::Reach(3, 4)), r#"{"Reach":[3,4]}"#);
+        assert_eq!(
+            to_json(Event::Pickup { item: 'x', held: 2 }),
+            r#"{"Pickup":{"item":"x","held":2}}"#
+        );
+        assert_eq!(to_json(Event::Tick), r#""Tick""#);
+    }
+
+    #[test]
+    fn event_log_test() {
+        let path = env::temp_dir().join(format!(
+            "adventurers_event_log_{}.jsonl",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+
+        let map = [((3, 2), Block::Sand)].into_iter().collect();
+        let quest = ron::from_str::<QuestDef>("Within(Walk(Sand), 2, Ticks, Fail)").unwrap();
+        let mut engine = Engine::new(map, Player::default(), &quest);
+        engine.event_log = Some(EventLog::create(path).unwrap());
+
+        engine.tick();
+        engine.do_move(CardinalDirection::East);
+        engine.tick();
+        engine.finish_event_log().unwrap();
+
+        let contents = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+        let records: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        // ticks that don't end the quest are not logged, and the quest is
+        // complete after moving onto sand, so nothing else is registered
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0],
+            json!({
+                "type": "event",
+                "event": { "Move": "Sand" },
+                "position": { "x": 3, "y": 2 },
+                "health": 10,
+                "status": "Complete",
+            })
+        );
+        assert_eq!(records[1]["type"], json!("summary"));
+        assert_eq!(records[1]["events"], json!(1));
+        assert_eq!(records[1]["won"], json!(true));
+        assert_eq!(records[1]["quest"]["kind"], json!({ "Within": "Ticks" }));
+    }
+
+    /// A tick is logged when the time limit runs out
+    #[test]
+    fn time_limit_log_test() {
+        let path = env::temp_dir().join(format!(
+            "adventurers_time_limit_log_{}.jsonl",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+
+        let quest = ron::from_str::<QuestDef>("Within(Walk(Sand), 2, Ticks, Fail)").unwrap();
+        let mut engine = Engine::new(Map::new(), Player::default(), &quest);
+        engine.event_log = Some(EventLog::create(path).unwrap());
+        engine.tick();
+        engine.tick();
+        engine.finish_event_log().unwrap();
+
+        let contents = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+        let records: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["event"], json!("Tick"));
+        assert_eq!(records[0]["status"], json!("Failed"));
+        assert_eq!(records[1]["events"], json!(1));
+        assert_eq!(records[1]["failed"], json!(true));
+    }
+}
@@ -0,0 +1,474 @@
+//! # Generate module
+//!
+//! Generate playable maps from a seed and a size, with
+//! `adventurers generate <new map file> [--seed <seed>] [--size <width>x<height>]`
+//!
+//! The world is bordered by barriers, and its biomes of grass, sand, water and
+//! rock come from noise. Lakes with sandy beaches are dug into it, each exactly
+//! [`LAKE_SPAN`] blocks across so that the player can swim straight across one
+//! at full health, e.g. for `WalkRepeat(Water, 9)`. Objects and signposts are
+//! scattered over the land, with enough `'x'` and `'y'` objects for the
+//! built-in quests
+//!
+//! Generated maps are only blocks, with no title or spawn point, so they are
+//! written in the plain map format that any version of the game can read.
+//! The player spawns in a clearing at the default spawn point, and all the land
+//! can be reached from it, with sand bridges built where needed. The same seed
+//! and size always generate the same map, since the randomness comes from the
+//! seed alone
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use blocks::Block;
+
+use crate::config::PlayerConfig;
+use crate::movement::{CardinalDirection, Coordinate};
+use crate::Map;
+
+/// The number of blocks of water across the middle of each lake
+pub const LAKE_SPAN: i32 = 9;
+
+/// The smallest world that a lake fits in, beside the spawn point
+pub const MIN_SIZE: (i32, i32) = (24, 12);
+
+/// The largest world that is generated, to keep maps a reasonable size
+pub const MAX_SIZE: (i32, i32) = (1000, 1000);
+
+/// The number of blocks of world for each lake
+const AREA_PER_LAKE: i32 = 1200;
+
+/// The number of blocks of world for each object, of each kind
+const AREA_PER_OBJECT: i32 = 150;
+
+/// The objects scattered over the land, and the fewest of each that there are
+const OBJECTS: [(char, i32); 2] = [('x', 5), ('y', 3)];
+
+/// A small, fast random number generator (SplitMix64), so that maps only
+/// depend on their seed
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a new random number generator from a seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.state)
+    }
+
+    /// A random number from `0` up to but not including `bound`
+    pub fn below(&mut self, bound: u32) -> u32 {
+        (self.next_u64() % u64::from(bound.max(1))) as u32
+    }
+
+    /// A random number from `min` up to and including `max`
+    pub fn between(&mut self, min: i32, max: i32) -> i32 {
+        min + self.below((max - min + 1) as u32) as i32
+    }
+}
+
+/// Scramble the bits of a number, as the last step of SplitMix64
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Smooth random noise from 0 to 1, which changes over about `scale` blocks
+///
+/// Random values at the corners of a grid are blended together, at a few
+/// scales from coarse to fine
+fn noise(seed: u64, x: i32, y: i32, scale: f64) -> f64 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut amplitudes = 0.0;
+    let mut scale = scale;
+    for octave in 0..3u64 {
+        total += amplitude
+            * value_noise(
+                mix(seed ^ octave),
+                f64::from(x) / scale,
+                f64::from(y) / scale,
+            );
+        amplitudes += amplitude;
+        amplitude /= 2.0;
+        scale /= 2.0;
+    }
+    total / amplitudes
+}
+
+/// Noise from 0 to 1, blending the random values at the corners of a grid
+fn value_noise(seed: u64, x: f64, y: f64) -> f64 {
+    let corner = |cx: f64, cy: f64| {
+        let hash = mix(seed ^ mix((cx as i64 as u64) ^ mix(cy as i64 as u64)));
+        (hash >> 11) as f64 / (1u64 << 53) as f64
+    };
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let top = corner(x0, y0) * (1.0 - tx) + corner(x0 + 1.0, y0) * tx;
+    let bottom = corner(x0, y0 + 1.0) * (1.0 - tx) + corner(x0 + 1.0, y0 + 1.0) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// A lake, as an ellipse of water
+#[derive(Debug, Clone, Copy)]
+struct Lake {
+    centre: Coordinate,
+    /// Whether the lake is longest from north to south rather than east to west
+    vertical: bool,
+    /// The distance from the centre to the side of the lake, across its width
+    half_width: i32,
+}
+
+impl Lake {
+    /// Whether the position is water in the lake
+    fn contains(&self, position: Coordinate) -> bool {
+        let (mut along, mut across) = (position.x - self.centre.x, position.y - self.centre.y);
+        if self.vertical {
+            (along, across) = (across, along);
+        }
+        // the middle row of the lake is exactly LAKE_SPAN blocks long
+        let half_length = f64::from(LAKE_SPAN + 1) / 2.0;
+        let along = f64::from(along) / half_length;
+        let across = f64::from(across) / f64::from(self.half_width + 1);
+        along * along + across * across < 1.0
+    }
+
+    /// The positions in or around the lake, including its beach
+    fn bounds(&self) -> (Coordinate, Coordinate) {
+        let (mut x, mut y) = ((LAKE_SPAN + 1) / 2 + 1, self.half_width + 1);
+        if self.vertical {
+            (x, y) = (y, x);
+        }
+        (
+            Coordinate::new(self.centre.x - x, self.centre.y - y),
+            Coordinate::new(self.centre.x + x, self.centre.y + y),
+        )
+    }
+
+    /// The beach block at the start of the swim straight across the lake
+    fn start(&self) -> Coordinate {
+        let offset = (LAKE_SPAN + 1) / 2;
+        if self.vertical {
+            Coordinate::new(self.centre.x, self.centre.y - offset)
+        } else {
+            Coordinate::new(self.centre.x - offset, self.centre.y)
+        }
+    }
+}
+
+/// Generate a map of the given size from a seed
+///
+/// # Errors
+///
+/// If the size is smaller than [`MIN_SIZE`] or larger than [`MAX_SIZE`]
+pub fn generate(seed: u64, size: (i32, i32)) -> Result<Map, String> {
+    let (width, height) = size;
+    if width < MIN_SIZE.0 || height < MIN_SIZE.1 || width > MAX_SIZE.0 || height > MAX_SIZE.1 {
+        return Err(format!(
+            "Maps must be from {}x{} to {}x{} blocks, not {width}x{height}",
+            MIN_SIZE.0, MIN_SIZE.1, MAX_SIZE.0, MAX_SIZE.1
+        ));
+    }
+
+    let mut rng = Rng::new(seed);
+    let spawn = PlayerConfig::default().spawn;
+    let inside = |position: Coordinate| {
+        (1..width - 1).contains(&position.x) && (1..height - 1).contains(&position.y)
+    };
+
+    // the biomes, from noise
+    let (height_seed, rock_seed) = (rng.next_u64(), rng.next_u64());
+    let mut map = Map::new();
+    for y in 0..height {
+        for x in 0..width {
+            let block = if !inside(Coordinate::new(x, y)) {
+                Block::Barrier
+            } else {
+                match noise(height_seed, x, y, 16.0) {
+                    h if h < 0.3 => Block::Water,
+                    h if h < 0.38 => Block::Sand,
+                    _ if noise(rock_seed, x, y, 8.0) > 0.7 => Block::Rock,
+                    _ => Block::Grass,
+                }
+            };
+            map.insert((x, y), block);
+        }
+    }
+
+    // a clearing around the spawn point
+    for card_dir in CardinalDirection::ALL {
+        map.insert((spawn + card_dir).into(), Block::Grass);
+    }
+    map.insert(spawn.into(), Block::Grass);
+
+    let lakes = dig_lakes(&mut map, &mut rng, size, spawn);
+    build_bridges(&mut map, spawn, &lakes);
+
+    // signposts, and objects on the rest of the land
+    map.insert(
+        (spawn + CardinalDirection::East).into(),
+        Block::Sign(format!(
+            "Welcome, wanderer! This world grew from seed {seed}"
+        )),
+    );
+    for lake in &lakes {
+        map.insert(
+            lake.start().into(),
+            Block::Sign(format!(
+                "Swim straight across from here: {LAKE_SPAN} blocks of water"
+            )),
+        );
+    }
+    scatter_objects(&mut map, &mut rng, size, spawn);
+
+    Ok(map)
+}
+
+/// Dig lakes with sandy beaches into the map, away from the spawn point and
+/// each other
+///
+/// # Returns
+///
+/// The lakes dug, of which there is always at least one
+fn dig_lakes(map: &mut Map, rng: &mut Rng, size: (i32, i32), spawn: Coordinate) -> Vec<Lake> {
+    let (width, height) = size;
+    let count = (width * height / AREA_PER_LAKE).max(1);
+    let mut lakes: Vec<Lake> = Vec::new();
+
+    // give up on a lake after a while, but always try hard for the first one
+    for attempt in 0..count * 20 + 1000 {
+        if lakes.len() as i32 == count || (attempt >= count * 20 && !lakes.is_empty()) {
+            break;
+        }
+        let vertical = height >= LAKE_SPAN + 4 && rng.below(2) == 0;
+        let half_width = rng.between(1, 3);
+        let mut lake = Lake {
+            centre: Coordinate::new(0, 0),
+            vertical,
+            half_width,
+        };
+        let (min, max) = lake.bounds();
+        let (reach_x, reach_y) = ((max.x - min.x) / 2, (max.y - min.y) / 2);
+        if width - 2 < 2 * reach_x + 1 || height - 2 < 2 * reach_y + 1 {
+            continue;
+        }
+        lake.centre = Coordinate::new(
+            rng.between(1 + reach_x, width - 2 - reach_x),
+            rng.between(1 + reach_y, height - 2 - reach_y),
+        );
+
+        let (min, max) = lake.bounds();
+        let overlaps = |other: &Lake| {
+            let (other_min, other_max) = other.bounds();
+            min.x <= other_max.x + 1
+                && other_min.x <= max.x + 1
+                && min.y <= other_max.y + 1
+                && other_min.y <= max.y + 1
+        };
+        let near_spawn = (min.x - 2..=max.x + 2).contains(&spawn.x)
+            && (min.y - 2..=max.y + 2).contains(&spawn.y);
+        if near_spawn || lakes.iter().any(overlaps) {
+            continue;
+        }
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let position = Coordinate::new(x, y);
+                let block = if lake.contains(position) {
+                    Block::Water
+                } else {
+                    Block::Sand
+                };
+                map.insert((x, y), block);
+            }
+        }
+        lakes.push(lake);
+    }
+    lakes
+}
+
+/// Turn water into sand to connect all the land to the spawn point, without
+/// crossing the lakes
+///
+/// The land that can't be reached is connected by the shortest bridge, until
+/// all of it can be reached
+fn build_bridges(map: &mut Map, spawn: Coordinate, lakes: &[Lake]) {
+    let is_land =
+        |block: Option<&Block>| !matches!(block, None | Some(Block::Barrier | Block::Water));
+    let in_lake = |position: Coordinate| lakes.iter().any(|lake| lake.contains(position));
+
+    loop {
+        let reached = flood(map, spawn, is_land);
+        let unreached: BTreeSet<(i32, i32)> = map
+            .iter()
+            .filter(|&(position, block)| is_land(Some(block)) && !reached.contains(position))
+            .map(|(&position, _)| position)
+            .collect();
+        if unreached.is_empty() {
+            return;
+        }
+
+        // search from all the land reached at once, through water that isn't a lake
+        let mut previous: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+        let mut queue: VecDeque<(i32, i32)> = reached.iter().copied().collect();
+        let mut end = None;
+        while let Some(position) = queue.pop_front() {
+            if unreached.contains(&position) {
+                end = Some(position);
+                break;
+            }
+            for card_dir in CardinalDirection::ALL {
+                let next = Coordinate::new(position.0, position.1) + card_dir;
+                let block = map.get(&next.into());
+                let passable = matches!(block, Some(Block::Water)) && !in_lake(next)
+                    || unreached.contains(&next.into());
+                if passable
+                    && !reached.contains(&next.into())
+                    && !previous.contains_key(&next.into())
+                {
+                    previous.insert(next.into(), position);
+                    queue.push_back(next.into());
+                }
+            }
+        }
+
+        let Some(mut position) = end else {
+            // the land is walled in by lakes or the border, so is left as it is
+            return;
+        };
+        while let Some(&before) = previous.get(&position) {
+            if map.get(&position) == Some(&Block::Water) {
+                map.insert(position, Block::Sand);
+            }
+            position = before;
+        }
+    }
+}
+
+/// The positions connected to the start whose blocks can be walked over
+fn flood(
+    map: &Map,
+    start: Coordinate,
+    walkable: impl Fn(Option<&Block>) -> bool,
+) -> BTreeSet<(i32, i32)> {
+    let mut reached = BTreeSet::from([start.into()]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(position) = queue.pop_front() {
+        for card_dir in CardinalDirection::ALL {
+            let next = position + card_dir;
+            if walkable(map.get(&next.into())) && reached.insert(next.into()) {
+                queue.push_back(next);
+            }
+        }
+    }
+    reached
+}
+
+/// Scatter objects over the grass and sand, away from the spawn point
+fn scatter_objects(map: &mut Map, rng: &mut Rng, size: (i32, i32), spawn: Coordinate) {
+    let near_spawn = |(x, y): (i32, i32)| (x - spawn.x).abs() + (y - spawn.y).abs() <= 1;
+    // the positions of each block are in order, so the same seed scatters the
+    // same objects
+    let mut free: Vec<(i32, i32)> = map
+        .positions_of(&Block::Grass)
+        .chain(map.positions_of(&Block::Sand))
+        .filter(|&position| !near_spawn(position))
+        .collect();
+
+    let per_kind = size.0 * size.1 / AREA_PER_OBJECT;
+    for (object, fewest) in OBJECTS {
+        for _ in 0..per_kind.max(fewest) {
+            if free.is_empty() {
+                return;
+            }
+            let position = free.swap_remove(rng.below(free.len() as u32) as usize);
+            map.insert(position, Block::Object(object));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use adventurers_quest::definition::QuestDef;
+
+    use super::*;
+    use crate::map_file::{MapFile, Regions};
+    use crate::validate::{self, Severity};
+
+    #[test]
+    fn seed_test() {
+        let map = generate(42, (60, 30)).unwrap();
+        assert_eq!(generate(42, (60, 30)).unwrap(), map);
+        assert_ne!(generate(43, (60, 30)).unwrap(), map);
+
+        assert_eq!(map.len(), 60 * 30);
+        assert_eq!(map[&(0, 0)], Block::Barrier);
+        assert_eq!(map[&(59, 29)], Block::Barrier);
+        assert!(generate(1, (10, 10)).is_err());
+
+        // written in the plain format
+        let map_file = MapFile {
+            map,
+            ..MapFile::default()
+        };
+        assert!(map_file.to_ron().unwrap().starts_with("{\n"));
+    }
+
+    /// Generated maps have no errors, and the built-in quests can be played on them
+    #[test]
+    fn playable_test() {
+        let quests = [
+            include_str!("../../quests/q1.ron"),
+            include_str!("../../quests/q2.ron"),
+            include_str!("../../quests/q3.ron"),
+        ];
+        for (seed, size) in [
+            (0, MIN_SIZE),
+            (1, (80, 40)),
+            (7, (40, 60)),
+            (12345, (150, 50)),
+        ] {
+            let map = generate(seed, size).unwrap();
+            for quest in quests {
+                let quest = ron::from_str::<QuestDef>(quest).unwrap();
+                let diagnostics = validate::validate_map(
+                    &map,
+                    &Regions::new(),
+                    &PlayerConfig::default(),
+                    Some(&quest),
+                );
+                assert!(
+                    diagnostics
+                        .iter()
+                        .all(|diagnostic| diagnostic.severity == Severity::Warning),
+                    "seed {seed}: {diagnostics:?}"
+                );
+            }
+
+            // every lake has a swim across it, starting from its signpost
+            let signs = map.iter().filter(
+                |(_, block)| matches!(block, Block::Sign(text) if text.starts_with("Swim")),
+            );
+            let mut lakes = 0;
+            for (&(x, y), _) in signs {
+                let across = [(1, 0), (0, 1)].into_iter().any(|(dx, dy)| {
+                    let far_side = &map[&(x + dx * (LAKE_SPAN + 1), y + dy * (LAKE_SPAN + 1))];
+                    (1..=LAKE_SPAN).all(|i| map[&(x + dx * i, y + dy * i)] == Block::Water)
+                        && matches!(far_side, Block::Sand | Block::Object(_))
+                });
+                assert!(across, "seed {seed}: no swim from ({x}, {y})");
+                lakes += 1;
+            }
+            assert!(lakes >= 1, "seed {seed}");
+        }
+    }
+}
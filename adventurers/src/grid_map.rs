@@ -0,0 +1,398 @@
+//! # Grid map module
+//!
+//! Read and write map files in the grid format, where the map is drawn as text
+//! with one character per block. The grid comes after an optional RON header,
+//! separated by a `---` line:
+//!
+//! ```text
+//! (
+//!     title: "Beach",
+//!     origin: (0, 0),
+//!     legend: {
+//!         'x': Object('x'),
+//!         '1': Sign("Hello, wanderer!"),
+//!     },
+//!     player: (spawn: (1, 1)),
+//!     regions: {
+//!         "beach": (min: (4, 1), max: (4, 2)),
+//!     },
+//! )
+//! ---
+//! ######
+//! #..x:#
+//! #1~~:#
+//! ######
+//! ```
+//!
+//! The origin is the coordinate of the top left character of the grid, and is
+//! `(0, 0)` unless given. A space is always empty space, with no block, and the
+//! other characters are looked up in the legend. The header's legend adds to
+//! the default legend, and can replace its characters:
+//!
+//! | Character | Block       |
+//! |-----------|-------------|
+//! | `#`       | Barrier     |
+//! | `=`       | Cinderblock |
+//! | `*`       | Flowerbush  |
+//! | `.`       | Grass       |
+//! | `^`       | Rock        |
+//! | `:`       | Sand        |
+//! | `~`       | Water       |
+//!
+//! Objects and signs always need an entry in the header's legend, which is
+//! where the text of each sign is kept. Everything in the header is optional,
+//! and a file that doesn't start with the header's `(` is just the grid, where
+//! a `---` line is a row of the grid like any other. Lines may end with either
+//! `\n` or `\r\n`
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::Display;
+
+use adventurers_map::Bounds;
+use blocks::Block;
+use serde::{Deserialize, Serialize};
+
+use crate::config::PlayerOverrides;
+use crate::map_file::{MapFile, Regions};
+use crate::serde_util::{some, unwrap_some};
+use crate::Map;
+
+/// The line between the header and the grid
+const SEPARATOR: &str = "---";
+
+/// The characters of the blocks that don't need to be in a header's legend
+const DEFAULT_LEGEND: [(char, Block); 7] = [
+    ('#', Block::Barrier),
+    ('=', Block::Cinderblock),
+    ('*', Block::Flowerbush),
+    ('.', Block::Grass),
+    ('^', Block::Rock),
+    (':', Block::Sand),
+    ('~', Block::Water),
+];
+
+/// The header of a grid map file
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct GridHeader {
+    /// The name of the map
+    #[serde(
+        deserialize_with = "some",
+        serialize_with = "unwrap_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    title: Option<String>,
+    /// The coordinate of the top left character of the grid
+    origin: (i32, i32),
+    /// The blocks of the characters that aren't in the default legend, or that
+    /// replace it
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    legend: BTreeMap<char, Block>,
+    /// The player's starting settings for this map
+    #[serde(skip_serializing_if = "PlayerOverrides::is_empty")]
+    player: PlayerOverrides,
+    /// The named regions of the map
+    #[serde(skip_serializing_if = "Regions::is_empty")]
+    regions: Regions,
+}
+
+/// A problem reading a grid map file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridError {
+    /// The line of the file the problem is on, starting from 1
+    pub line: usize,
+    /// The column of the line the problem is at, starting from 1
+    pub col: usize,
+    /// A description of the problem
+    pub message: String,
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.col, self.message
+        )
+    }
+}
+
+impl Error for GridError {}
+
+/// Read the contents of a grid map file
+///
+/// # Errors
+///
+/// If the header is not a correctly formatted RON value, the legend has an entry
+/// for a space, or the grid has a character that isn't in the legend
+pub fn from_str(contents: &str) -> Result<MapFile, GridError> {
+    let lines: Vec<&str> = contents
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    let has_header = contents.trim_start().starts_with('(');
+    let separator = lines.iter().position(|line| line.trim_end() == SEPARATOR);
+    let (header, grid_start) = match separator.filter(|_| has_header) {
+        Some(separator) => {
+            let header =
+                ron::from_str::<GridHeader>(&lines[..separator].join("\n")).map_err(|e| {
+                    GridError {
+                        line: e.position.line,
+                        col: e.position.col,
+                        message: e.code.to_string(),
+                    }
+                })?;
+            (header, separator + 1)
+        }
+        None if has_header => {
+            return Err(GridError {
+                line: lines.len(),
+                col: 1,
+                message: format!("The header must be followed by a {SEPARATOR} line"),
+            })
+        }
+        None => (GridHeader::default(), 0),
+    };
+
+    if header.legend.contains_key(&' ') {
+        return Err(GridError {
+            line: 1,
+            col: 1,
+            message: String::from("A space is always empty space, so it can't be in the legend"),
+        });
+    }
+    let legend: HashMap<char, Block> = DEFAULT_LEGEND.into_iter().chain(header.legend).collect();
+
+    let (origin_x, origin_y) = header.origin;
+    let mut blocks = Map::new();
+    for (row, line) in lines[grid_start..].iter().enumerate() {
+        for (col, c) in line.chars().enumerate().filter(|&(_, c)| c != ' ') {
+            let block = legend.get(&c).ok_or_else(|| GridError {
+                line: grid_start + row + 1,
+                col: col + 1,
+                message: format!("'{c}' is not in the legend"),
+            })?;
+            blocks.insert(
+                (origin_x + col as i32, origin_y + row as i32),
+                block.clone(),
+            );
+        }
+    }
+
+    Ok(MapFile {
+        map: blocks,
+        title: header.title,
+        regions: header.regions,
+        player: header.player,
+    })
+}
+
+/// Write a map file in the grid format, which reads back as the same map file
+///
+/// The grid starts at the top left corner of the blocks. Objects are drawn as
+/// themselves where possible, and every other block not in the default legend
+/// is given a spare character
+///
+/// # Errors
+///
+/// If the header can't be written as RON
+pub fn to_string(map_file: &MapFile) -> Result<String, ron::Error> {
+    let Bounds {
+        min: (min_x, min_y),
+        max: (max_x, max_y),
+    } = map_file.map.bounds().unwrap_or(Bounds {
+        min: (0, 0),
+        max: (-1, -1),
+    });
+
+    let legend = legend_for(&map_file.map);
+    let character = |block: &Block| {
+        DEFAULT_LEGEND
+            .iter()
+            .chain(&legend)
+            .find(|(_, b)| b == block)
+            .map(|&(c, _)| c)
+    };
+
+    let header = GridHeader {
+        title: map_file.title.clone(),
+        origin: (min_x, min_y),
+        legend: legend.iter().cloned().collect(),
+        player: map_file.player,
+        regions: map_file.regions.clone(),
+    };
+    let mut contents = ron::ser::to_string_pretty(&header, ron::ser::PrettyConfig::default())?;
+    contents.push('\n');
+    contents.push_str(SEPARATOR);
+    contents.push('\n');
+
+    for y in min_y..=max_y {
+        let row: String = (min_x..=max_x)
+            .map(|x| map_file.map.get(&(x, y)).and_then(character).unwrap_or(' '))
+            .collect();
+        contents.push_str(row.trim_end());
+        contents.push('\n');
+    }
+    Ok(contents)
+}
+
+/// The legend entries needed for the blocks of the map that aren't in the
+/// default legend
+///
+/// Objects are given their own character unless it is already taken, and are
+/// given characters before signs so that signs don't take them
+fn legend_for(map: &Map) -> Vec<(char, Block)> {
+    let mut blocks: Vec<(&(i32, i32), &Block)> = map.iter().collect();
+    blocks.sort_by_key(|&(&position, block)| (!matches!(block, Block::Object(_)), position));
+
+    let mut spare = ('0'..='9')
+        .chain('A'..='Z')
+        .chain('a'..='z')
+        .chain("!$%&+/;<>?@_|".chars())
+        .chain('\u{c0}'..=char::MAX);
+    let mut legend: Vec<(char, Block)> = Vec::new();
+    for (_, block) in blocks {
+        let known = |(_, b): &(char, Block)| b == block;
+        if DEFAULT_LEGEND.iter().any(known) || legend.iter().any(known) {
+            continue;
+        }
+
+        // the separator is made of '-', so it is never used for a block
+        let is_free = |c: &char| {
+            !c.is_control()
+                && !matches!(c, ' ' | '-')
+                && !DEFAULT_LEGEND
+                    .iter()
+                    .chain(&legend)
+                    .any(|&(used, _)| used == *c)
+        };
+        let c = match block {
+            Block::Object(c) if is_free(c) => *c,
+            _ => spare
+                .find(is_free)
+                .expect("there are more spare characters than blocks on any map"),
+        };
+        legend.push((c, block.clone()));
+    }
+    legend
+}
+
+#[cfg(test)]
+mod tests {
+    use adventurers_quest::quests::Rect;
+
+    use super::*;
+
+    #[test]
+    fn from_str_test() {
+        let contents = "(
+            origin: (-1, 5),
+            legend: {'x': Object('x'), '1': Sign(\"Hello, wanderer!\"), '.': Sand},
+        )
+        ---
+        #x
+         1.";
+        let contents: String = contents
+            .lines()
+            .map(|line| line.trim_start_matches("        "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let map_file = from_str(&contents).unwrap();
+        assert_eq!(
+            map_file.map,
+            Map::from([
+                ((-1, 5), Block::Barrier),
+                ((0, 5), Block::Object('x')),
+                ((0, 6), Block::Sign(String::from("Hello, wanderer!"))),
+                ((1, 6), Block::Sand),
+            ])
+        );
+
+        // no header
+        let map_file = from_str("~~\n::").unwrap();
+        assert_eq!(map_file.map[&(1, 1)], Block::Sand);
+
+        // Windows line endings
+        let map_file = from_str("(origin: (1, 1))\r\n---\r\n~~\r\n::\r\n").unwrap();
+        assert_eq!(
+            map_file.map,
+            from_str("(origin: (1, 1))\n---\n~~\n::").unwrap().map
+        );
+
+        // without a header, a --- line is part of the grid
+        assert_eq!(
+            from_str("~~\n---\n::").unwrap_err(),
+            GridError {
+                line: 2,
+                col: 1,
+                message: String::from("'-' is not in the legend"),
+            }
+        );
+        assert_eq!(
+            from_str("(origin: (1, 1))\n~~").unwrap_err().message,
+            "The header must be followed by a --- line"
+        );
+
+        assert_eq!(
+            from_str("(legend: {})\n---\n..\n.y").unwrap_err(),
+            GridError {
+                line: 4,
+                col: 2,
+                message: String::from("'y' is not in the legend"),
+            }
+        );
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let map = Map::from([
+            ((-3, -2), Block::Grass),
+            ((0, 0), Block::Object('#')),
+            ((1, 0), Block::Object('q')),
+            ((2, 0), Block::Object('-')),
+            ((4, 1), Block::Sign(String::from("Don't \"panic\"\nhere"))),
+            ((5, 1), Block::Sign(String::from("Another sign"))),
+            ((5, 2), Block::Water),
+        ]);
+        let map_file = MapFile {
+            map,
+            title: Some(String::from("Don't panic")),
+            regions: Regions::from([(
+                String::from("pond"),
+                Rect {
+                    min: (5, 2),
+                    max: (5, 2),
+                },
+            )]),
+            player: PlayerOverrides {
+                spawn: Some((-3, -2)),
+                health: Some(3),
+                icon: None,
+            },
+        };
+
+        let contents = to_string(&map_file).unwrap();
+        assert!(contents.contains("\n---\n.\n\n   0q1\n       23\n        ~\n"));
+        assert_eq!(from_str(&contents).unwrap(), map_file);
+    }
+
+    /// The shipped maps are drawn the same in both formats
+    #[test]
+    fn shipped_maps_test() {
+        for (ron, grid) in [
+            (
+                include_str!("../../maps/full_game.ron"),
+                include_str!("../../maps/full_game.grid"),
+            ),
+            (
+                include_str!("../../maps/testing_game.ron"),
+                include_str!("../../maps/testing_game.grid"),
+            ),
+        ] {
+            let map_file: MapFile = MapFile::from_str(ron).unwrap();
+            assert_eq!(from_str(grid).unwrap(), map_file);
+            assert_eq!(to_string(&map_file).unwrap(), grid);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! # HUD module
+//!
+//! The heads-up display is a status line kept on the top row of the viewport,
+//! which the player never walks into. It shows the player's health, position
+//! and inventory, the viewport, and what to do next for the quest
+//!
+//! The HUD is drawn onto the screen rather than shown as a message, so that it
+//! doesn't replace the messages from signs. The map editor draws its own status
+//! line the same way
+
+use adventurers_quest::progress::{QuestKind, QuestProgress};
+use adventurers_quest::quests::combinators::TimeUnit;
+use adventurers_quest::QuestStatus;
+use termgame::{Game, GameColor, GameStyle, StyledCharacter};
+
+use crate::config::TICK_DURATION;
+use crate::engine::Engine;
+use crate::movement::Coordinate;
+
+/// The separator between the parts of the status line
+const SEPARATOR: &str = " │ ";
+
+/// The most health shown as one heart per point of health
+const MAX_HEARTS: i32 = 10;
+
+/// The status line for the current state of the game, given the position of the
+/// top left corner of the viewport
+///
+/// The line is cut short to the given width
+pub fn status_line(engine: &Engine, viewport: Coordinate, width: usize) -> String {
+    let player = &engine.player;
+    let position = player.position();
+    let progress = engine.quest.progress();
+
+    let mut parts = vec![
+        health_bar(player.health(), player.max_health()),
+        format!("({}, {})", position.x, position.y),
+        format!("View ({}, {})", viewport.x, viewport.y),
+        format!("{} objects", player.inventory().total()),
+    ];
+    parts.extend(time_left(&progress));
+    parts.push(quest_summary(&progress));
+
+    join(&parts, width)
+}
+
+/// Join the parts of a status line, cutting it short to the given width
+pub fn join(parts: &[String], width: usize) -> String {
+    let line = parts.join(SEPARATOR);
+    if line.chars().count() > width {
+        line.chars().take(width - 1).chain(['…']).collect()
+    } else {
+        line
+    }
+}
+
+/// Render a status line across the top row of the viewport, padded to the
+/// given width
+///
+/// # Returns
+///
+/// The positions rendered over, to be rendered again once the HUD moves
+pub fn render(game: &mut Game, line: &str, width: i32) -> Vec<Coordinate> {
+    let vp = game.get_viewport();
+    let characters = line.chars().chain(std::iter::repeat(' '));
+    let style = || {
+        GameStyle::new()
+            .color(Some(GameColor::White))
+            .background_color(Some(GameColor::Black))
+    };
+    (vp.x..vp.x + width)
+        .zip(characters)
+        .map(|(x, c)| {
+            game.set_screen_char(x, vp.y, Some(StyledCharacter::new(c).style(style())));
+            Coordinate::new(x, vp.y)
+        })
+        .collect()
+}
+
+/// A heart for each point of health, or the health as a number if there would
+/// be too many hearts
+fn health_bar(health: i32, max_health: i32) -> String {
+    if max_health > MAX_HEARTS {
+        return format!("♥ {health}/{max_health}");
+    }
+
+    let max_health = max_health.max(0);
+    let health = health.clamp(0, max_health) as usize;
+    format!(
+        "{}{}",
+        "♥".repeat(health),
+        "♡".repeat(max_health as usize - health)
+    )
+}
+
+/// The time left on the first ongoing time limit of the quest, if any
+///
+/// Ticks are shown as the seconds they take at normal speed
+fn time_left(progress: &QuestProgress) -> Option<String> {
+    let within = progress.find_ongoing(&|node| matches!(node.kind, QuestKind::Within(_)))?;
+    let (QuestKind::Within(unit), Some(count)) = (within.kind, within.count) else {
+        return None;
+    };
+
+    let remaining = count.target.saturating_sub(count.current);
+    Some(match unit {
+        TimeUnit::Ticks => format!("{:.1}s left", (TICK_DURATION * remaining).as_secs_f64()),
+        TimeUnit::Moves => format!("{} left", unit.amount(remaining)),
+    })
+}
+
+/// A one line summary of the first ongoing quest with no sub-quests, or of the
+/// whole quest if there is none, e.g. once it is over
+fn quest_summary(progress: &QuestProgress) -> String {
+    let Some(leaf) = progress.find_ongoing(&|node| node.children.is_empty()) else {
+        return match progress.status {
+            QuestStatus::Complete => String::from("Quest complete"),
+            QuestStatus::Failed => String::from("Quest failed"),
+            QuestStatus::Ongoing => progress.description.clone(),
+        };
+    };
+
+    match leaf.count {
+        Some(count) => format!("{} ({}/{})", leaf.description, count.current, count.target),
+        None => leaf.description.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use adventurers_quest::definition::QuestDef;
+    use blocks::Block;
+
+    use super::*;
+    use crate::movement::CardinalDirection;
+    use crate::player::Player;
+
+    #[test]
+    fn status_line_test() {
+        let map = [((3, 2), Block::Water), ((4, 2), Block::Object('x'))]
+            .into_iter()
+            .collect();
+        let quest = ron::from_str::<QuestDef>("Then(Walk(Water), Walk(Sand))").unwrap();
+        let mut engine = Engine::new(map, Player::default(), &quest);
+        engine.do_move(CardinalDirection::East);
+        engine.do_move(CardinalDirection::East);
+        engine.do_move(CardinalDirection::East);
+
+        assert_eq!(
+            status_line(&engine, Coordinate::new(-1, -2), 78),
+            "♥♥♥♥♥♥♥♥♥♥ │ (5, 2) │ View (-1, -2) │ 1 objects │ Walk on a Sand block"
+        );
+
+        engine.player.take_damage(3);
+        assert_eq!(health_bar(7, 20), "♥ 7/20");
+        assert_eq!(health_bar(-2, 3), "♡♡♡");
+        assert_eq!(health_bar(-2, -1), "");
+        engine.quest = ron::from_str::<QuestDef>("Within(Walk(Sand), 20, Ticks, Fail)")
+            .unwrap()
+            .build();
+        engine.tick();
+        // the line is cut short to fit in the viewport
+        let line = status_line(&engine, Coordinate::new(-1, -2), 78);
+        assert_eq!(
+            line,
+            "♥♥♥♥♥♥♥♡♡♡ │ (5, 2) │ View (-1, -2) │ 1 objects │ 0.9s left │ Walk on a Sand …"
+        );
+        assert_eq!(line.chars().count(), 78);
+    }
+}
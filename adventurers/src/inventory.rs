@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// The objects collected by the player
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    /// The number of each object held, keyed by the object's character
+    items: BTreeMap<char, u32>,
+}
+
+impl Inventory {
+    /// Add an object to the inventory
+    ///
+    /// # Returns
+    ///
+    /// The number of these objects now held
+    pub fn add(&mut self, item: char) -> u32 {
+        let count = self.items.entry(item).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// The number of each object held, keyed by the object's character
+    pub fn items(&self) -> &BTreeMap<char, u32> {
+        &self.items
+    }
+
+    /// The total number of objects held
+    pub fn total(&self) -> u32 {
+        self.items.values().sum()
+    }
+}
+
+impl Display for Inventory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.items.is_empty() {
+            return write!(f, "You have not collected any objects");
+        }
+
+        let lines: Vec<String> = self
+            .items
+            .iter()
+            .map(|(item, count)| format!("'{item}' x {count}"))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
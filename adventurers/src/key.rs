@@ -0,0 +1,67 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use termgame::{GameEvent, KeyCode, SimpleEvent};
+
+/// A key press that can be stored in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+    /// A function key, e.g. `F(5)`
+    F(u8),
+    /// A character key
+    Char(char),
+    /// A character key pressed while holding Control
+    Ctrl(char),
+}
+
+impl Key {
+    /// Convert a [`GameEvent`] into a key press
+    ///
+    /// Returns `None` if the event is not a supported key press, e.g. a mouse event
+    pub fn from_event(event: GameEvent) -> Option<Key> {
+        match event.into() {
+            SimpleEvent::Just(code) => match code {
+                KeyCode::Up => Some(Key::Up),
+                KeyCode::Down => Some(Key::Down),
+                KeyCode::Left => Some(Key::Left),
+                KeyCode::Right => Some(Key::Right),
+                KeyCode::Enter => Some(Key::Enter),
+                KeyCode::Esc => Some(Key::Esc),
+                KeyCode::Backspace => Some(Key::Backspace),
+                KeyCode::Tab => Some(Key::Tab),
+                KeyCode::F(n) => Some(Key::F(n)),
+                KeyCode::Char(ch) => Some(Key::Char(ch)),
+                _ => None,
+            },
+            SimpleEvent::WithControl(KeyCode::Char(ch)) => Some(Key::Ctrl(ch)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Key {
+    /// Show the key as it is labelled on a keyboard, with characters quoted
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Key::Up => write!(f, "Up"),
+            Key::Down => write!(f, "Down"),
+            Key::Left => write!(f, "Left"),
+            Key::Right => write!(f, "Right"),
+            Key::Enter => write!(f, "Enter"),
+            Key::Esc => write!(f, "Esc"),
+            Key::Backspace => write!(f, "Backspace"),
+            Key::Tab => write!(f, "Tab"),
+            Key::F(n) => write!(f, "F{n}"),
+            Key::Char(ch) => write!(f, "'{ch}'"),
+            Key::Ctrl(ch) => write!(f, "Ctrl-{}", ch.to_ascii_uppercase()),
+        }
+    }
+}
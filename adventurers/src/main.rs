@@ -1,182 +1,697 @@
+mod args;
+mod bindings;
 mod config;
+mod editor;
+mod engine;
+mod event_log;
+mod generate;
+mod grid_map;
+mod hud;
+mod inventory;
+mod key;
+mod map_file;
 mod movement;
 mod player;
+mod recording;
+mod save;
+mod script;
+mod serde_util;
+mod solver;
+#[cfg(test)]
+mod testing;
+mod tiled;
+mod validate;
 
-use std::collections::HashMap;
 use std::error::Error;
-use std::time::Duration;
-use std::{env, fs};
-
-use adventurers_quest::quests::combinators::{QuestMajority, QuestRepeat, QuestThen};
-use adventurers_quest::quests::{WalkQuest, WalkRepeatQuest};
-use adventurers_quest::{Event, Quest};
-use blocks::Block;
-use movement::CardinalDirection;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, process};
+
+use adventurers_quest::definition::QuestDef;
+use adventurers_quest::Event;
+use args::Command;
+use bindings::{Action, KeyBindings};
+use config::{ConfigFile, Viewport, ViewportConfig};
+use engine::{Engine, Outcome};
+use event_log::EventLog;
+use key::Key;
+use map_file::{MapFile, MapFormat, Regions};
+use movement::{CardinalDirection, Coordinate};
 use player::Player;
+use recording::Recording;
+use save::SaveGame;
+use solver::Solution;
 use termgame::{
     run_game, Controller, Game, GameEvent, GameSettings, KeyCode, Message, SimpleEvent,
+    StyledCharacter, SCREEN_HEIGHT, SCREEN_WIDTH,
 };
 
-/// The game map
-pub type Map = HashMap<(i32, i32), Block>;
+use adventurers_map::Bounds;
+pub use adventurers_map::Map;
+
+/// The smallest and largest coordinates of the blocks on the map, including the
+/// given position (e.g. the spawn point), with a margin of one block on each side
+///
+/// The player can walk on the empty space around the map, but there is no
+/// reason to go further than one block beyond its edges
+pub fn map_bounds(map: &Map, position: Coordinate) -> (Coordinate, Coordinate) {
+    let position = position.into();
+    let Bounds { min, max } = map
+        .bounds()
+        .map_or(Bounds::at(position), |bounds| bounds.including(position))
+        .grow(1);
+    (Coordinate::new(min.0, min.1), Coordinate::new(max.0, max.1))
+}
+
+/// Render the block of the map at the given position onto the [`Game`] screen,
+/// or clear the position if there is no block there
+pub fn render_block(game: &mut Game, map: &Map, position: Coordinate) {
+    game.set_screen_char(
+        position.x,
+        position.y,
+        map.get(&position.into()).map(|block| block.clone().into()),
+    );
+}
 
 /// The state of the adventurers game
 struct MyGame {
-    /// The player of the game
-    player: Player,
-    /// The map of the game
-    map: Map,
-    /// The quest being completed
-    quest: Box<dyn Quest<Event>>,
+    /// The rules and state of the game
+    engine: Engine,
+    /// The map of the game as it was before any changes were made to it
+    initial_map: Map,
+    /// The description of the quest, which saves are checked against
+    quest: QuestDef,
+    /// The file name the game is saved to
+    save_file: String,
+    /// The number of game ticks that have passed
+    ticks: u64,
+    /// The recording of the key presses of this session, if it is being recorded
+    recording: Option<Recording>,
+    /// The recorded session being replayed, if any
+    replay: Option<Recording>,
+    /// The route highlighted by the last hint, cleared on the next key press
+    hint: Vec<Coordinate>,
+    /// The positions the HUD was last rendered at
+    hud: Vec<Coordinate>,
+    /// The action triggered by each key
+    keys: KeyBindings,
+    /// The viewport settings from the config file
+    viewport_config: ViewportConfig,
+    /// The size of the viewport, and how it follows the player
+    viewport: Viewport,
 }
 
 impl MyGame {
-    /// Parse a RON file containing a [`Map`] value
+    /// Parse a quest argument
     ///
-    /// The file name must be passed in as the first command line argument
+    /// The argument is either the name of a built-in quest (`q1`, `q2` or `q3`),
+    /// or the file name of a RON file containing a [`QuestDef`] value
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// If the first command line argument is not a correctly formatted RON file with a [`Map`] value
+    /// If the argument is neither a built-in quest nor a readable and correctly
+    /// formatted RON file with a [`QuestDef`] value
     ///
     /// # Returns
     ///
-    /// A [`Map`] object
-    fn parse_map() -> Map {
-        let map_file = env::args()
-            .nth(1)
-            .expect("You failed to provide a map filename");
-        let contents = fs::read_to_string(map_file).expect("Failed to read map file to string");
-        ron::from_str(&contents).expect("Failed to read map file as RON")
+    /// The [`QuestDef`] describing the quest
+    fn parse_quest_def(quest_arg: &str) -> Result<QuestDef, Box<dyn Error>> {
+        let contents = match quest_arg {
+            "q1" => String::from(include_str!("../../quests/q1.ron")),
+            "q2" => String::from(include_str!("../../quests/q2.ron")),
+            "q3" => String::from(include_str!("../../quests/q3.ron")),
+            path => fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read quest file {path}: {e}"))?,
+        };
+        Ok(ron::from_str(&contents)
+            .map_err(|e| format!("Failed to read quest file {quest_arg} as RON: {e}"))?)
     }
 
-    /// Parse the second command line argument as a quest number
-    ///
-    /// Currently only `q1`, `q2` and `q3` are supported
+    /// Parse a quest argument into the [`QuestDef`] of a quest to be played on
+    /// a map with the given named regions
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// If the second command line argument is not either `q1`, `q2` or `q3`
-    ///
-    /// # Returns
+    /// See [`MyGame::parse_quest_def`]. Also if the quest can never be
+    /// completed, or refers to a region that the map doesn't define
+    fn parse_quest(quest_arg: &str, regions: &Regions) -> Result<QuestDef, Box<dyn Error>> {
+        let quest = Self::parse_quest_def(quest_arg)?;
+        if let Some(problem) = quest.impossible().first() {
+            return Err(format!("The quest can never be completed: {problem}").into());
+        }
+        if let Some(name) = validate::undefined_regions(regions, &quest).first() {
+            return Err(format!(
+                "The quest needs a region called \"{name}\", but the map doesn't define one"
+            )
+            .into());
+        }
+        Ok(quest)
+    }
+
+    /// Render the contained map state onto the [`Game`] screen
     ///
-    /// A new [`Quest`]
-    fn parse_quest() -> Box<dyn Quest<Event>> {
-        let quest_no = env::args()
-            .nth(2)
-            .expect("You failed to provide a quest (q1, q2 or q3)");
-
-        match &*quest_no {
-            "q1" => Box::new(QuestRepeat::new(Box::new(WalkQuest::new(Block::Sand)), 5)),
-            "q2" => Box::new(QuestThen::new(
-                Box::new(QuestRepeat::new(
-                    Box::new(WalkQuest::new(Block::Object('x'))),
-                    5,
-                )),
-                Box::new(QuestRepeat::new(
-                    Box::new(WalkQuest::new(Block::Object('y'))),
-                    3,
-                )),
-            )),
-            "q3" => Box::new(QuestMajority::new(
-                Box::new(QuestThen::new(
-                    Box::new(QuestRepeat::new(Box::new(WalkQuest::new(Block::Sand)), 5)),
-                    Box::new(WalkQuest::new(Block::Object('x'))),
-                )),
-                Box::new(QuestThen::new(
-                    Box::new(WalkQuest::new(Block::Object('x'))),
-                    Box::new(WalkQuest::new(Block::Grass)),
+    /// Blocks that have been removed from the map are cleared from the screen
+    fn render_map(&mut self, game: &mut Game) {
+        self.initial_map
+            .keys()
+            .for_each(|&(x, y)| self.render_block(game, Coordinate::new(x, y)));
+    }
+
+    /// Render the block at the given position onto the [`Game`] screen, or clear
+    /// the position if there is no block there
+    fn render_block(&self, game: &mut Game, position: Coordinate) {
+        render_block(game, &self.engine.map, position);
+    }
+
+    /// Render the outcomes of an action onto the [`Game`] screen
+    fn render_outcomes(&mut self, game: &mut Game, outcomes: Vec<Outcome>) {
+        for outcome in outcomes {
+            match outcome {
+                Outcome::Moved { from, .. } => {
+                    game.set_message(None);
+                    self.render_block(game, from);
+                    self.engine.player.render(game, &self.viewport);
+                }
+                Outcome::Removed { position, block } => {
+                    // Keep the background of the removed block, without its character
+                    let sc: StyledCharacter = block.into();
+                    game.set_screen_char(position.x, position.y, Some(sc.character(' ')));
+                }
+                Outcome::Message(message) => game.set_message(Some(Message::new(message))),
+                Outcome::Died => {
+                    game.set_message(Some(Message::new(String::from("You drowned :("))))
+                }
+                Outcome::Won => game.set_message(Some(
+                    Message::new(String::from(
+                        "You completed all quests! Press any key to quit",
+                    ))
+                    .title(String::from("You won!")),
                 )),
-                Box::new(QuestRepeat::new(
-                    Box::new(WalkRepeatQuest::new(Block::Water, 9)),
-                    2,
+                Outcome::Lost => game.set_message(Some(
+                    Message::new(format!(
+                        "You failed the quest! Press {} to restart, or any other key to quit",
+                        self.keys.describe(Action::ResetQuest)
+                    ))
+                    .title(String::from("You lost!")),
                 )),
-            )),
-            _ => panic!("That quest does not exist!"),
+                Outcome::Blocked { .. } | Outcome::Damaged { .. } => {}
+            }
         }
     }
 
-    /// Render the contained map state onto the [`Game`] screen
-    fn render_map(&mut self, game: &mut Game) {
-        self.map.iter().for_each(|((x, y), block)| {
-            game.set_screen_char(*x, *y, Some(block.clone().into()));
-        });
+    /// Move the player and render the outcome
+    fn do_move(&mut self, game: &mut Game, card_dir: CardinalDirection) {
+        let outcomes = self.engine.do_move(card_dir);
+        self.render_outcomes(game, outcomes);
+    }
+
+    /// Highlight the route to the nearest block that makes progress on the quest
+    fn show_hint(&mut self, game: &mut Game) {
+        let message = match self.engine.route_to_target() {
+            Some((route, target)) => {
+                let place = match target {
+                    Event::Move(Some(block)) => format!("the nearest {block} block"),
+                    Event::InRegion(name) => name,
+                    _ => {
+                        let destination = route[route.len() - 1];
+                        format!("({}, {})", destination.x, destination.y)
+                    }
+                };
+                let message = format!("Follow the dots to {place}, {} moves away", route.len());
+                self.hint = route;
+                self.render_hint(game);
+                message
+            }
+            None if self.engine.hint_targets().is_empty() => {
+                String::from("There is nowhere to go that would help with the quest right now")
+            }
+            None => String::from("None of the places the quest needs can be reached from here"),
+        };
+        game.set_message(Some(Message::new(message).title(String::from("Hint"))));
+    }
+
+    /// Render the hinted route over the map, except where the player is
+    fn render_hint(&self, game: &mut Game) {
+        let player_position = self.engine.player.position();
+        for (i, &position) in self.hint.iter().enumerate() {
+            if position == player_position {
+                continue;
+            }
+
+            let icon = if i == self.hint.len() - 1 {
+                '◎'
+            } else {
+                '·'
+            };
+            let sc = game.get_screen_char(position.x, position.y);
+            game.set_screen_char(
+                position.x,
+                position.y,
+                Some(sc.map_or(icon.into(), |styled_character| {
+                    styled_character.character(icon)
+                })),
+            );
+        }
+    }
+
+    /// Remove the hinted route from the screen
+    fn clear_hint(&mut self, game: &mut Game) {
+        let player_position = self.engine.player.position();
+        for position in std::mem::take(&mut self.hint) {
+            if position != player_position {
+                self.render_block(game, position);
+            }
+        }
+    }
+
+    /// Render the HUD over the reserved rows at the top of the viewport
+    fn render_hud(&mut self, game: &mut Game) {
+        self.clear_hud(game);
+
+        let vp = game.get_viewport();
+        let width = self.viewport.size.0;
+        let line = hud::status_line(&self.engine, Coordinate::new(vp.x, vp.y), width as usize);
+        self.hud = hud::render(game, &line, width);
+    }
+
+    /// Remove the HUD from the screen, e.g. before the viewport moves, except
+    /// where the player is
+    fn clear_hud(&mut self, game: &mut Game) {
+        let player_position = self.engine.player.position();
+        for position in std::mem::take(&mut self.hud) {
+            if position != player_position {
+                self.render_block(game, position);
+            }
+        }
+    }
+
+    /// Save the game to the save file, showing whether it succeeded
+    fn save(&self, game: &mut Game) {
+        let result =
+            SaveGame::new(&self.engine, &self.initial_map, &self.quest).write(&self.save_file);
+        let message = match result {
+            Ok(()) => format!("Game saved to {}", self.save_file),
+            Err(e) => e.to_string(),
+        };
+        game.set_message(Some(Message::new(message).title(String::from("Save"))));
+    }
+
+    /// Load the game from the save file, showing whether it succeeded
+    fn load(&mut self, game: &mut Game) {
+        let old_position = self.engine.player.position();
+        let result = SaveGame::read(&self.save_file)
+            .and_then(|save| save.apply(&mut self.engine, &self.initial_map, &self.quest));
+        let message = match result {
+            Ok(()) => format!("Game loaded from {}", self.save_file),
+            Err(e) => e.to_string(),
+        };
+
+        self.render_map(game);
+        self.render_block(game, old_position);
+        self.engine.player.render(game, &self.viewport);
+        game.set_message(Some(Message::new(message).title(String::from("Load"))));
     }
 }
 
 impl Controller for MyGame {
     fn on_start(&mut self, game: &mut Game) {
+        self.viewport = self.viewport_config.resolve(game.screen_size());
         self.render_map(game);
-        self.player.render(game);
+        self.engine.player.render(game, &self.viewport);
+        self.render_hud(game);
+        self.replay_keys(game);
     }
 
     fn on_event(&mut self, game: &mut Game, event: GameEvent) {
-        // end the game if the player has already won
-        if self.player.won {
+        // key presses are ignored while a session is being replayed
+        if self.replay.is_some() {
+            return;
+        }
+
+        let key = Key::from_event(event);
+        let action = key.and_then(|key| self.keys.action(key));
+        if let (Some(recording), Some(_)) = (&mut self.recording, key) {
+            recording.record(self.ticks, action);
+        }
+        self.handle_action(game, action);
+        self.render_hud(game);
+    }
+
+    fn on_tick(&mut self, game: &mut Game) {
+        self.ticks += 1;
+        let outcomes = self.engine.tick();
+        self.render_outcomes(game, outcomes);
+        self.replay_keys(game);
+        self.render_hud(game);
+    }
+}
+
+impl MyGame {
+    /// Handle a key press, given the action it is bound to, either from the
+    /// player or from a replay
+    fn handle_action(&mut self, game: &mut Game, action: Option<Action>) {
+        self.clear_hint(game);
+
+        // end the game if the player has already won or died
+        if self.engine.player.won || self.engine.player.is_dead() {
             game.end_game();
         }
 
-        match event.into() {
-            // move up
-            SimpleEvent::Just(KeyCode::Up) => self.player.do_move(
-                game,
-                &mut self.map,
-                &mut self.quest,
-                CardinalDirection::North,
-            ),
-
-            // move right
-            SimpleEvent::Just(KeyCode::Right) => self.player.do_move(
-                game,
-                &mut self.map,
-                &mut self.quest,
-                CardinalDirection::East,
-            ),
-
-            // move down
-            SimpleEvent::Just(KeyCode::Down) => self.player.do_move(
-                game,
-                &mut self.map,
-                &mut self.quest,
-                CardinalDirection::South,
-            ),
-
-            // move left
-            SimpleEvent::Just(KeyCode::Left) => self.player.do_move(
-                game,
-                &mut self.map,
-                &mut self.quest,
-                CardinalDirection::West,
-            ),
+        // offer a restart if the player has failed the quest
+        if self.engine.player.failed {
+            if action == Some(Action::ResetQuest) {
+                let old_position = self.engine.player.position();
+                self.engine.restart();
+                self.render_map(game);
+                self.render_block(game, old_position);
+                self.engine.player.render(game, &self.viewport);
+                game.set_message(None);
+            } else {
+                game.end_game();
+            }
+            return;
+        }
+
+        let Some(action) = action else {
+            return;
+        };
+        match action {
+            Action::MoveNorth => self.do_move(game, CardinalDirection::North),
+            Action::MoveEast => self.do_move(game, CardinalDirection::East),
+            Action::MoveSouth => self.do_move(game, CardinalDirection::South),
+            Action::MoveWest => self.do_move(game, CardinalDirection::West),
 
             // check quest status
-            SimpleEvent::Just(KeyCode::Char('q')) => game.set_message(Some(
-                Message::new(self.quest.to_string()).title(String::from("Quest")),
+            Action::ShowQuest => game.set_message(Some(
+                Message::new(self.engine.quest.to_string()).title(String::from("Quest")),
             )),
 
-            // reset quest
-            SimpleEvent::Just(KeyCode::Char('r')) => self.quest.reset(),
-            _ => {}
+            // check inventory
+            Action::ShowInventory => game.set_message(Some(
+                Message::new(self.engine.player.inventory().to_string()).title(format!(
+                    "Inventory ({} objects)",
+                    self.engine.player.inventory().total()
+                )),
+            )),
+
+            Action::ShowHint => self.show_hint(game),
+            Action::ResetQuest => self.engine.quest.reset(),
+            // the save file is left alone by recorded sessions, so they replay the same
+            Action::Save | Action::Load if self.recording.is_some() || self.replay.is_some() => {
+                game.set_message(Some(Message::new(String::from(
+                    "Saving and loading are turned off while a session is recorded or replayed",
+                ))))
+            }
+            Action::Save => self.save(game),
+            Action::Load => self.load(game),
+            Action::Quit => game.end_game(),
+        }
+    }
+
+    /// Handle the replayed key presses that happened by the current tick
+    ///
+    /// Once the replay has finished, the player can keep playing from there
+    fn replay_keys(&mut self, game: &mut Game) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+
+        let mut keys = Vec::new();
+        while let Some(key) = replay.next_key(self.ticks) {
+            keys.push(key);
+        }
+        let finished = replay.keys.is_empty();
+
+        for key in keys {
+            self.handle_action(game, key.action);
+        }
+        if finished {
+            self.replay = None;
+            game.set_message(Some(
+                Message::new(String::from(
+                    "The replay has finished, you can now keep playing",
+                ))
+                .title(String::from("Replay")),
+            ));
+        }
+    }
+}
+
+/// Check a map, and optionally a quest, printing any problems found
+///
+/// # Returns
+///
+/// Whether the map is free of errors
+fn validate_map(
+    map_file: &str,
+    quest_arg: Option<&str>,
+    config_file: Option<&str>,
+) -> Result<bool, Box<dyn Error>> {
+    let contents = fs::read_to_string(map_file)
+        .map_err(|e| format!("Failed to read map file {map_file}: {e}"))?;
+    let quest = quest_arg.map(MyGame::parse_quest_def).transpose()?;
+    let player = ConfigFile::load(config_file)?.player();
+
+    let diagnostics =
+        validate::validate(&contents, MapFormat::of(map_file), &player, quest.as_ref());
+    for diagnostic in &diagnostics {
+        println!("{map_file}: {diagnostic}");
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == validate::Severity::Error)
+        .count();
+    println!(
+        "{map_file}: {errors} errors, {} warnings",
+        diagnostics.len() - errors
+    );
+    Ok(errors == 0)
+}
+
+/// Convert a map file to the format of the new map file
+fn convert_map(map_file: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let map = MapFile::read(map_file)?;
+    map.write(output)?;
+    println!("{map_file}: converted {} blocks to {output}", map.map.len());
+    Ok(())
+}
+
+/// Generate a map from a seed, or a seed chosen from the time if none is given,
+/// writing it in the format of the new map file
+fn generate_map(output: &str, seed: Option<u64>, size: (i32, i32)) -> Result<(), Box<dyn Error>> {
+    let seed = match seed {
+        Some(seed) => seed,
+        None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    let map = MapFile {
+        map: generate::generate(seed, size)?,
+        ..MapFile::default()
+    };
+    map.write(output)?;
+    println!(
+        "Generated a {}x{} map from seed {seed} to {output}",
+        size.0, size.1
+    );
+    Ok(())
+}
+
+/// Import a Tiled map, writing it in the format of the new map file
+fn import_map(tiled_file: &str, mapping_file: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let map = tiled::import(tiled_file, mapping_file)?;
+    map.write(output)?;
+    println!(
+        "{tiled_file}: imported {} blocks and {} regions to {output}",
+        map.map.len(),
+        map.regions.len()
+    );
+    Ok(())
+}
+
+/// Search for moves that complete a quest on a map, printing them as a script,
+/// or why the quest can't be completed
+///
+/// # Returns
+///
+/// Whether moves completing the quest were found
+fn solve_quest(
+    map_file: &str,
+    quest_arg: &str,
+    max_states: usize,
+    config_file: Option<&str>,
+) -> Result<bool, Box<dyn Error>> {
+    let contents = MapFile::read(map_file)?;
+    let quest = MyGame::parse_quest_def(quest_arg)?;
+    let player = ConfigFile::load(config_file)?
+        .player()
+        .with(&contents.player);
+    player.check()?;
+    let MapFile { map, regions, .. } = contents;
+
+    // don't search for regions or blocks that can't be found anyway
+    let impossible = quest.impossible();
+    let undefined = validate::undefined_regions(&regions, &quest);
+    let missing = validate::missing_quest_blocks(&map, &player, &quest);
+    if !impossible.is_empty() || !undefined.is_empty() || !missing.is_empty() {
+        println!("{quest_arg} can't be completed on {map_file}");
+        for problem in impossible {
+            println!("{problem}");
+        }
+        for name in undefined {
+            println!("The quest needs a region called \"{name}\", but the map doesn't define one");
+        }
+        for block in missing {
+            println!("The quest needs a {block} block, but the player can't reach one");
         }
+        return Ok(false);
     }
 
-    fn on_tick(&mut self, _game: &mut Game) {}
+    let mut engine = Engine::new(map, Player::new(&player), &quest);
+    engine.regions = regions;
+    match solver::solve(&mut engine, max_states) {
+        Solution::Solved(moves) => {
+            println!(
+                "# Completes {quest_arg} on {map_file} in {} moves",
+                moves.len()
+            );
+            print!("{}", script::format_script(&moves));
+            Ok(true)
+        }
+        Solution::Impossible { states } => {
+            println!(
+                "{quest_arg} can't be completed on {map_file}: every way of playing it \
+                 fails the quest or drowns the player ({states} states explored)"
+            );
+            Ok(false)
+        }
+        Solution::GaveUp { states } => {
+            println!("Gave up after exploring {states} states, try a larger --max-states");
+            Ok(false)
+        }
+    }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args = match Command::parse()? {
+        Command::Play(args) => args,
+        Command::Validate { map, quest, config } => {
+            if !validate_map(&map, quest.as_deref(), config.as_deref())? {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Command::Edit { map, config } => {
+            return editor::run(&map, &ConfigFile::load(config.as_deref())?);
+        }
+        Command::Generate { output, seed, size } => {
+            generate_map(&output, seed, size)?;
+            return Ok(());
+        }
+        Command::Convert { map, output } => {
+            convert_map(&map, &output)?;
+            return Ok(());
+        }
+        Command::Import {
+            tiled,
+            mapping,
+            output,
+        } => {
+            import_map(&tiled, &mapping, &output)?;
+            return Ok(());
+        }
+        Command::Solve {
+            map,
+            quest,
+            max_states,
+            config,
+        } => {
+            if !solve_quest(&map, &quest, max_states, config.as_deref())? {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+    };
+    let config = ConfigFile::load(args.config.as_deref())?;
+    let keys = KeyBindings::new(&config.keys)?;
+    let map_file = MapFile::read(&args.map)?;
+    let player = config.player().with(&map_file.player);
+    player.check()?;
+    let MapFile { map, regions, .. } = map_file;
+    let quest = MyGame::parse_quest(&args.quest, &regions)?;
     let mut controller = MyGame {
-        player: Player::default(),
-        map: MyGame::parse_map(),
-        quest: MyGame::parse_quest(),
+        initial_map: map.clone(),
+        engine: Engine::new(map, Player::new(&player), &quest),
+        quest,
+        save_file: args.save_file(),
+        ticks: 0,
+        recording: None,
+        replay: None,
+        hint: vec![],
+        hud: vec![],
+        keys,
+        viewport_config: config.viewport,
+        // resolved again from the game's screen once it starts
+        viewport: config.viewport.resolve((SCREEN_WIDTH, (SCREEN_HEIGHT, 0))),
     };
+    controller.engine.regions = regions;
+
+    if let Some(save_file) = &args.load {
+        SaveGame::read(save_file)?.apply(
+            &mut controller.engine,
+            &controller.initial_map,
+            &controller.quest,
+        )?;
+    }
+
+    if let Some(jsonl_file) = &args.jsonl {
+        controller.engine.event_log = Some(EventLog::create(jsonl_file)?);
+    }
+
+    // play a script without a terminal, exiting unsuccessfully if the game was not won
+    if let Some(script_file) = &args.script {
+        let contents = fs::read_to_string(script_file)
+            .map_err(|e| format!("Failed to read script file {script_file}: {e}"))?;
+        let moves = script::parse_script(&contents)?;
+        let played = script::run_script(&mut controller.engine, &moves);
+        println!(
+            "{}",
+            script::report(&controller.engine, played, moves.len())
+        );
+        controller.engine.finish_event_log()?;
+
+        if !controller.engine.player.won {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(replay_file) = &args.replay {
+        let replay = Recording::read(replay_file)?;
+        if replay.map != args.map || replay.quest != args.quest {
+            eprintln!(
+                "Warning: {replay_file} was recorded with map {} and quest {}, so it may not replay correctly",
+                replay.map, replay.quest
+            );
+        }
+        controller.replay = Some(replay);
+    }
+    if args.record.is_some() {
+        controller.recording = Some(Recording::new(&args.map, &args.quest));
+    }
 
     run_game(
         &mut controller,
         GameSettings::new()
-            // The below are the defaults, but shown so you can edit them.
-            .tick_duration(Duration::from_millis(50))
+            // replays can be sped up, since quests only count ticks rather than time
+            .tick_duration(config::TICK_DURATION / args.speed)
+            // the same key as bindings::QUIT_KEY
             .quit_event(Some(SimpleEvent::WithControl(KeyCode::Char('c')).into())),
     )?;
+    controller.engine.finish_event_log()?;
+
+    if let (Some(record_file), Some(recording)) = (&args.record, &controller.recording) {
+        recording.write(record_file)?;
+        println!("Session recorded to {record_file}");
+    }
 
     println!("Game Ended!");
 
@@ -0,0 +1,284 @@
+//! # Map file module
+//!
+//! Read map files, which come in two formats. The original format is just the
+//! blocks of the map, as a RON map from coordinates to blocks:
+//!
+//! ```text
+//! {
+//!     (0, 0): Grass,
+//!     (1, 0): Sand,
+//! }
+//! ```
+//!
+//! The extended format also names regions of the map, which quests can refer
+//! to, and can override the player's starting settings from the config file
+//! (see [`crate::config`]). Each region is a rectangle, including its corners:
+//!
+//! ```text
+//! (
+//!     title: "Beach",
+//!     player: (spawn: (1, 0), health: 20),
+//!     blocks: {
+//!         (0, 0): Grass,
+//!         (1, 0): Sand,
+//!     },
+//!     regions: {
+//!         "northern cave": (min: (0, -10), max: (8, -4)),
+//!     },
+//! )
+//! ```
+//!
+//! Everything but the blocks is optional
+//!
+//! Files ending in `.grid` are instead drawn as text, as described in
+//! [`crate::grid_map`]. Either format can be converted to the other without
+//! losing anything
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use adventurers_quest::quests::Rect;
+use serde::Deserialize;
+
+use crate::config::PlayerOverrides;
+use crate::serde_util::some;
+use crate::{grid_map, Map};
+
+/// The named regions of a map
+pub type Regions = BTreeMap<String, Rect>;
+
+/// The formats a map file can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapFormat {
+    /// The blocks as a RON map from coordinates to blocks
+    Ron,
+    /// The blocks drawn as text, see [`crate::grid_map`]
+    Grid,
+}
+
+impl MapFormat {
+    /// The format of a map file, given by its extension: `.grid` files are
+    /// grids, and anything else is RON
+    pub fn of(path: &str) -> Self {
+        match Path::new(path).extension() {
+            Some(extension) if extension == "grid" => MapFormat::Grid,
+            _ => MapFormat::Ron,
+        }
+    }
+}
+
+/// The contents of a map file
+#[derive(Debug, Default, PartialEq)]
+pub struct MapFile {
+    /// The blocks of the map
+    pub map: Map,
+    /// The name of the map, which is unset in the original format
+    pub title: Option<String>,
+    /// The named regions of the map, which are empty in the original format
+    pub regions: Regions,
+    /// The player's starting settings for this map, including the spawn point,
+    /// which are all unset in the original format
+    pub player: PlayerOverrides,
+}
+
+/// The extended format of a map file, as it is written
+#[derive(Deserialize)]
+struct ExtendedMapFile {
+    /// The name of the map
+    #[serde(default, deserialize_with = "some")]
+    title: Option<String>,
+    /// The player's starting settings for this map
+    #[serde(default)]
+    player: PlayerOverrides,
+    /// The blocks of the map
+    blocks: Map,
+    /// The named regions of the map
+    #[serde(default)]
+    regions: Regions,
+}
+
+impl MapFile {
+    /// Read the contents of a map file in either format
+    ///
+    /// # Errors
+    ///
+    /// If the contents are in neither format. The error is the one from the
+    /// format that could be read the furthest, which is most likely the format
+    /// that was intended
+    pub fn from_str(contents: &str) -> Result<Self, ron::error::SpannedError> {
+        let original_error = match ron::from_str::<Map>(contents) {
+            Ok(map) => {
+                return Ok(Self {
+                    map,
+                    ..Self::default()
+                })
+            }
+            Err(e) => e,
+        };
+
+        match ron::from_str::<ExtendedMapFile>(contents) {
+            Ok(extended) => Ok(Self {
+                map: extended.blocks,
+                title: extended.title,
+                regions: extended.regions,
+                player: extended.player,
+            }),
+            Err(extended_error) => {
+                let position = |e: &ron::error::SpannedError| (e.position.line, e.position.col);
+                Err(if position(&extended_error) > position(&original_error) {
+                    extended_error
+                } else {
+                    original_error
+                })
+            }
+        }
+    }
+
+    /// Read a map file in the format given by its extension
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or is not a correctly formatted map file
+    pub fn read(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read map file {path}: {e}"))?;
+        Ok(match MapFormat::of(path) {
+            MapFormat::Ron => Self::from_str(&contents)
+                .map_err(|e| format!("Failed to read map file {path} as RON: {e}"))?,
+            MapFormat::Grid => grid_map::from_str(&contents)
+                .map_err(|e| format!("Failed to read map file {path} as a grid: {e}"))?,
+        })
+    }
+
+    /// Write the map file in the format given by its extension
+    ///
+    /// # Errors
+    ///
+    /// If the map file cannot be written
+    pub fn write(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let contents = match MapFormat::of(path) {
+            MapFormat::Ron => self.to_ron()?,
+            MapFormat::Grid => grid_map::to_string(self)?,
+        };
+        fs::write(path, contents).map_err(|e| format!("Failed to write map file {path}: {e}"))?;
+        Ok(())
+    }
+
+    /// Write the map file as RON, with one block per line in order of their
+    /// coordinates
+    ///
+    /// The original format is used unless there is a title, regions or player
+    /// settings
+    ///
+    /// # Errors
+    ///
+    /// If a block or setting can't be written as RON
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        let player = &self.player;
+        let title = &self.title;
+        let extended = title.is_some() || !self.regions.is_empty() || !player.is_empty();
+        let indent = if extended { "    " } else { "" };
+
+        let mut contents = String::new();
+        if extended {
+            contents.push_str("(\n");
+            if let Some(title) = title {
+                contents.push_str(&format!("    title: {},\n", ron::to_string(title)?));
+            }
+            if !player.is_empty() {
+                contents.push_str(&format!("    player: {},\n", ron::to_string(player)?));
+            }
+            contents.push_str("    blocks: ");
+        }
+
+        contents.push_str("{\n");
+        let blocks: BTreeMap<_, _> = self.map.iter().collect();
+        for ((x, y), block) in blocks {
+            contents.push_str(&format!(
+                "{indent}    ({x}, {y}): {},\n",
+                ron::to_string(block)?
+            ));
+        }
+        contents.push_str(indent);
+        contents.push('}');
+
+        if extended {
+            contents.push_str(",\n");
+            if !self.regions.is_empty() {
+                contents.push_str("    regions: {\n");
+                for (name, Rect { min, max }) in &self.regions {
+                    contents.push_str(&format!(
+                        "        {}: (min: ({}, {}), max: ({}, {})),\n",
+                        ron::to_string(name)?,
+                        min.0,
+                        min.1,
+                        max.0,
+                        max.1
+                    ));
+                }
+                contents.push_str("    },\n");
+            }
+            contents.push(')');
+        }
+        contents.push('\n');
+        Ok(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blocks::Block;
+
+    use super::*;
+
+    #[test]
+    fn formats_test() {
+        let original: MapFile = MapFile::from_str("{(0, 0): Grass, (1, 0): Sand}").unwrap();
+        assert_eq!(original.map.len(), 2);
+        assert!(original.regions.is_empty());
+
+        let extended: MapFile = MapFile::from_str(
+            r#"(
+                title: "Beach",
+                player: (spawn: (1, 0), icon: '@'),
+                blocks: {(0, 0): Grass, (1, 0): Sand},
+                regions: {"beach": (min: (1, 0), max: (1, 0))},
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(extended.title.as_deref(), Some("Beach"));
+        assert_eq!(extended.player.spawn, Some((1, 0)));
+        assert_eq!(
+            extended.map.iter().collect::<BTreeMap<_, _>>(),
+            original.map.iter().collect()
+        );
+        assert_eq!(
+            extended.regions["beach"],
+            Rect {
+                min: (1, 0),
+                max: (1, 0)
+            }
+        );
+        assert_eq!(extended.map[&(1, 0)], Block::Sand);
+        assert_eq!(extended.player.icon, Some('@'));
+        assert_eq!(extended.player.health, None);
+
+        // writing either format reads back the same
+        assert_eq!(
+            original.to_ron().unwrap(),
+            "{\n    (0, 0): Grass,\n    (1, 0): Sand,\n}\n"
+        );
+        assert_eq!(
+            MapFile::from_str(&extended.to_ron().unwrap()).unwrap(),
+            extended
+        );
+
+        // the error comes from the format that got further
+        let e = MapFile::from_str("(\n blocks: {(0, 0): Lava},\n)").unwrap_err();
+        assert_eq!(e.position.line, 2);
+        let e = MapFile::from_str("{\n(0, 0): Grass,\n(0, 1): Lava}").unwrap_err();
+        assert_eq!(e.position.line, 3);
+    }
+}
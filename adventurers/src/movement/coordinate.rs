@@ -1,9 +1,11 @@
 use std::ops::{Add, AddAssign};
 
+use serde::{Deserialize, Serialize};
+
 use super::{CardinalDirection, Direction};
 
 /// Represent a 2D coordinate.
-#[derive(Default, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Coordinate {
     pub x: i32,
     pub y: i32,
@@ -11,7 +13,7 @@ pub struct Coordinate {
 
 impl Coordinate {
     /// Create a new coordinate.
-    pub fn new(x: i32, y: i32) -> Coordinate {
+    pub const fn new(x: i32, y: i32) -> Coordinate {
         Coordinate { x, y }
     }
 }
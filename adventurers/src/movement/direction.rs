@@ -8,6 +8,7 @@ pub struct Direction {
 }
 
 /// Represent a cardinal direction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum CardinalDirection {
     North,
     East,
@@ -15,6 +16,16 @@ pub enum CardinalDirection {
     West,
 }
 
+impl CardinalDirection {
+    /// All of the cardinal directions
+    pub const ALL: [CardinalDirection; 4] = [
+        CardinalDirection::North,
+        CardinalDirection::East,
+        CardinalDirection::South,
+        CardinalDirection::West,
+    ];
+}
+
 impl Add for Direction {
     type Output = Direction;
 
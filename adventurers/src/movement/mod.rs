@@ -1,9 +1,11 @@
 //! # Movement module
 //!
-//! Represent position and directional movement
+//! Represent position and directional movement, and find routes around the map
 
 pub mod coordinate;
 pub mod direction;
+pub mod path;
 
 pub use coordinate::*;
 pub use direction::*;
+pub use path::*;
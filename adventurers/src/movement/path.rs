@@ -0,0 +1,122 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::{CardinalDirection, Coordinate};
+use crate::Map;
+
+/// A position, and the health the player has there
+type State = ((i32, i32), i32);
+
+/// Find the shortest route from the start to a position that passes the goal
+/// test, without walking through barriers or drowning
+///
+/// The player starts with the given health, which is restored to `max_health`
+/// on safe blocks.
+/// Only positions within the bounds are searched, e.g. the [`map_bounds`](crate::map_bounds)
+///
+/// # Returns
+///
+/// The positions along the route, ending with the goal but not including the
+/// start, or `None` if no goal can be reached
+pub fn shortest_path(
+    map: &Map,
+    start: Coordinate,
+    health: i32,
+    max_health: i32,
+    bounds: (Coordinate, Coordinate),
+    is_goal: impl Fn(Coordinate) -> bool,
+) -> Option<Vec<Coordinate>> {
+    let (min, max) = bounds;
+    let start_state = (start.into(), health);
+    let mut previous: HashMap<State, State> = HashMap::from([(start_state, start_state)]);
+    let mut queue = VecDeque::from([(start, health)]);
+
+    while let Some((position, health)) = queue.pop_front() {
+        for card_dir in CardinalDirection::ALL {
+            let next = position + card_dir;
+            if !(min.x..=max.x).contains(&next.x) || !(min.y..=max.y).contains(&next.y) {
+                continue;
+            }
+
+            let block = map.get(&next.into());
+            if block.is_some_and(|block| block.blocks_movement()) {
+                continue;
+            }
+            let next_health = match block.map_or(0, |block| block.damage()) {
+                0 => max_health,
+                damage => health - damage,
+            };
+            if next_health <= 0 || previous.contains_key(&(next.into(), next_health)) {
+                continue;
+            }
+            previous.insert((next.into(), next_health), (position.into(), health));
+
+            if is_goal(next) {
+                return Some(route(&previous, (next.into(), next_health), start_state));
+            }
+            queue.push_back((next, next_health));
+        }
+    }
+
+    None
+}
+
+/// Follow the states back from the end to the start state
+fn route(previous: &HashMap<State, State>, end: State, start: State) -> Vec<Coordinate> {
+    let mut route = Vec::new();
+    let mut state = end;
+    while state != start {
+        route.push(Coordinate::new(state.0 .0, state.0 .1));
+        state = previous[&state];
+    }
+    route.reverse();
+    route
+}
+
+#[cfg(test)]
+mod tests {
+    use blocks::Block;
+
+    use super::*;
+
+    const MAX_HEALTH: i32 = 10;
+
+    #[test]
+    fn shortest_path_test() {
+        use Block::*;
+        // a wall with a gap at the bottom, and deep water at the top
+        let mut map: Map = (0..4).map(|y| ((1, y), Barrier)).collect();
+        map.insert((1, 0), Water);
+        let bounds = (Coordinate::new(0, 0), Coordinate::new(2, 4));
+        let goal = |position| position == Coordinate::new(2, 0);
+
+        assert_eq!(
+            shortest_path(
+                &map,
+                Coordinate::new(0, 0),
+                MAX_HEALTH,
+                MAX_HEALTH,
+                bounds,
+                goal
+            ),
+            Some(vec![Coordinate::new(1, 0), Coordinate::new(2, 0)])
+        );
+
+        // with only 1 health left, the player must restore their health first
+        assert_eq!(
+            shortest_path(&map, Coordinate::new(0, 0), 1, MAX_HEALTH, bounds, goal),
+            Some(vec![
+                Coordinate::new(0, 1),
+                Coordinate::new(0, 0),
+                Coordinate::new(1, 0),
+                Coordinate::new(2, 0),
+            ])
+        );
+
+        assert_eq!(
+            shortest_path(&map, Coordinate::new(0, 0), 1, MAX_HEALTH, bounds, |_| {
+                false
+            }),
+            None
+        );
+    }
+}
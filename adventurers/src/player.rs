@@ -1,12 +1,12 @@
-use adventurers_quest::{Event, Quest, QuestStatus};
-use blocks::Block;
-use termgame::{Game, Message, StyledCharacter, ViewportLocation};
+use serde::{Deserialize, Serialize};
+use termgame::Game;
 
-use crate::config::{PLAYER_HEALTH, VP_BUFFER, VP_SIZE};
-use crate::movement::{CardinalDirection, Coordinate};
-use crate::Map;
+use crate::config::{PlayerConfig, Viewport};
+use crate::inventory::Inventory;
+use crate::movement::Coordinate;
 
 /// The player in the game
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     /// The icon to render the player as
     icon: char,
@@ -14,77 +14,84 @@ pub struct Player {
     position: Coordinate,
     /// The current health of the player
     health: i32,
+    /// The health the player has when fully restored
+    #[serde(default = "default_max_health")]
+    max_health: i32,
+    /// The objects the player has collected
+    inventory: Inventory,
     /// Whether the player has won the game or not
     pub won: bool,
+    /// Whether the player has failed the quest or not
+    pub failed: bool,
 }
 
 impl Player {
-    /// Move the player and update the game screen and quest
-    pub fn do_move(
-        &mut self,
-        game: &mut Game,
-        map: &mut Map,
-        quest: &mut Box<dyn Quest<Event>>,
-        card_dir: CardinalDirection,
-    ) {
-        if self.health == 0 {
-            game.end_game();
+    /// Create a new player at full health with the given starting settings
+    pub fn new(config: &PlayerConfig) -> Self {
+        Self {
+            icon: config.icon,
+            position: config.spawn,
+            health: config.health,
+            max_health: config.health,
+            inventory: Inventory::default(),
+            won: false,
+            failed: false,
         }
+    }
 
-        let old_pos = self.position;
-        let new_pos = self.position + card_dir;
-        let destination_block = map.get(&new_pos.into());
+    /// The current position of the player on the map
+    pub fn position(&self) -> Coordinate {
+        self.position
+    }
 
-        if let Some(destination_block) = destination_block {
-            // check if destination block can be walked on
-            if destination_block.blocks_movement() {
-                return;
-            }
+    /// The current health of the player
+    pub fn health(&self) -> i32 {
+        self.health
+    }
 
-            // check if destination block should prompt a message
-            if let Some(message) = destination_block.message() {
-                game.set_message(Some(Message::new(message)));
-            } else {
-                game.set_message(None);
-            }
+    /// The health the player has when fully restored
+    pub fn max_health(&self) -> i32 {
+        self.max_health
+    }
 
-            // check if destination block hurts the player
-            if destination_block.damage() != 0 {
-                self.health -= destination_block.damage();
-            } else {
-                // player restores health
-                self.health = PLAYER_HEALTH;
-            }
+    /// Whether the player has run out of health
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0
+    }
 
-            // check if the player has just died
-            if self.health == 0 {
-                game.set_message(Some(Message::new(String::from("You drowned :("))));
-            }
+    /// The objects the player has collected
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
 
-            // register event
-            if quest.register_event(&Event::on_block(destination_block.clone()))
-                == QuestStatus::Complete
-            {
-                self.won = true;
-                game.set_message(Some(
-                    Message::new(String::from(
-                        "You completed all quests! Press any key to quit",
-                    ))
-                    .title(String::from("You won!")),
-                ));
-            }
-        } else {
-            game.set_message(None);
-            self.health = PLAYER_HEALTH;
-        }
+    /// Move the player to a new position
+    pub fn move_to(&mut self, position: Coordinate) {
+        self.position = position;
+    }
 
-        self.position = new_pos;
-        self.reset_block(game, map, old_pos);
-        self.render(game);
+    /// Hurt the player by the given amount of damage
+    pub fn take_damage(&mut self, damage: i32) {
+        self.health -= damage;
+    }
+
+    /// Restore the player to full health
+    pub fn restore_health(&mut self) {
+        self.health = self.max_health;
+    }
+
+    /// Add an object to the player's inventory
+    ///
+    /// # Returns
+    ///
+    /// The number of these objects the player now holds
+    pub fn pick_up(&mut self, item: char) -> u32 {
+        self.inventory.add(item)
     }
 
     /// Render the player on the game screen
-    pub fn render(&self, game: &mut Game) {
+    ///
+    /// The viewport is moved to follow the player if needed
+    pub fn render(&self, game: &mut Game, viewport: &Viewport) {
         let sc = game.get_screen_char(self.position.x, self.position.y);
         game.set_screen_char(
             self.position.x,
@@ -93,60 +100,17 @@ impl Player {
                 styled_character.character(self.icon)
             })),
         );
-        self.move_viewport(game);
-    }
-
-    /// Reset a block on the game screen after it has been walked on
-    ///
-    /// After Object blocks are walked on however, they disappear
-    fn reset_block(&self, game: &mut Game, map: &mut Map, position: Coordinate) {
-        let block = map.get(&position.into());
-
-        if let Some(Block::Object(_)) = block {
-            let block = map.remove(&position.into());
-            game.set_screen_char(
-                position.x,
-                position.y,
-                block.map(|b| {
-                    let sc: StyledCharacter = b.into();
-                    sc.character(' ')
-                }),
-            );
-            return;
-        }
-
-        game.set_screen_char(position.x, position.y, block.map(|b| b.clone().into()));
-    }
-
-    /// Move the viewport if the player is close to the edge of the screen
-    fn move_viewport(&self, game: &mut Game) {
-        let vp = game.get_viewport();
-        let mut vp_x = vp.x;
-        let mut vp_y = vp.y;
-
-        if self.position.x - vp.x == VP_BUFFER {
-            vp_x -= 1;
-        } else if vp.x + VP_SIZE.0 - 1 - self.position.x == VP_BUFFER {
-            vp_x += 1;
-        }
-
-        if self.position.y - vp.y == VP_BUFFER {
-            vp_y -= 1;
-        } else if vp.y + VP_SIZE.1 - 1 - self.position.y == VP_BUFFER {
-            vp_y += 1;
-        }
-
-        game.set_viewport(ViewportLocation { x: vp_x, y: vp_y });
+        viewport.follow(game, self.position);
     }
 }
 
 impl Default for Player {
     fn default() -> Self {
-        Self {
-            icon: '♟',
-            position: Coordinate::new(2, 2),
-            health: PLAYER_HEALTH,
-            won: false,
-        }
+        Self::new(&PlayerConfig::default())
     }
 }
+
+/// The full health of players saved before it could be configured
+fn default_max_health() -> i32 {
+    PlayerConfig::default().health
+}
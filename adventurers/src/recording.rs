@@ -0,0 +1,111 @@
+//! # Recording module
+//!
+//! Record the key presses of a session along with the tick they happened on, so
+//! that the session can be replayed exactly
+//!
+//! Each key press is recorded as the action it was bound to, so that a replay
+//! plays out the same whatever key bindings are used to watch it
+//!
+//! Since quests only see game ticks rather than real time, a replay at any speed
+//! plays out the same way as the original session
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::Action;
+
+/// A key press made during a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedKey {
+    /// The number of game ticks that had passed when the key was pressed
+    pub tick: u64,
+    /// The action the key was bound to, if any
+    pub action: Option<Action>,
+}
+
+/// A recorded session, stored as RON to match the map files
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    /// The map file the session was played on
+    pub map: String,
+    /// The quest the session was played with
+    pub quest: String,
+    /// The key presses made during the session, in order
+    pub keys: VecDeque<RecordedKey>,
+}
+
+impl Recording {
+    /// Create a new empty recording of a session on the given map and quest
+    pub fn new(map: &str, quest: &str) -> Self {
+        Self {
+            map: String::from(map),
+            quest: String::from(quest),
+            keys: VecDeque::new(),
+        }
+    }
+
+    /// Record a key press, given the action it was bound to
+    pub fn record(&mut self, tick: u64, action: Option<Action>) {
+        self.keys.push_back(RecordedKey { tick, action });
+    }
+
+    /// Take the next key press that happened at or before the given tick
+    pub fn next_key(&mut self, tick: u64) -> Option<RecordedKey> {
+        if self.keys.front()?.tick <= tick {
+            self.keys.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Read a recording from a RON file
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or is not a correctly formatted recording
+    pub fn read(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read recording {path}: {e}"))?;
+        Ok(ron::from_str(&contents)
+            .map_err(|e| format!("Failed to read recording {path} as RON: {e}"))?)
+    }
+
+    /// Write the recording to a RON file
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be written
+    pub fn write(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, contents).map_err(|e| format!("Failed to write recording {path}: {e}"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_key_test() {
+        let mut recording = Recording::new("map.ron", "q1");
+        recording.record(0, Some(Action::MoveNorth));
+        recording.record(3, None);
+        recording.record(3, Some(Action::Save));
+
+        let recording: Recording = ron::from_str(&ron::to_string(&recording).unwrap()).unwrap();
+        let mut replay = recording.clone();
+        let action = |replay: &mut Recording, tick| replay.next_key(tick).map(|key| key.action);
+
+        assert_eq!(action(&mut replay, 0), Some(Some(Action::MoveNorth)));
+        assert_eq!(action(&mut replay, 0), None);
+        assert_eq!(action(&mut replay, 2), None);
+        assert_eq!(action(&mut replay, 3), Some(None));
+        assert_eq!(action(&mut replay, 3), Some(Some(Action::Save)));
+        assert_eq!(action(&mut replay, 4), None);
+        assert_eq!(recording.keys.len(), 3);
+    }
+}
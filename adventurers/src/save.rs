@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+
+use adventurers_quest::definition::QuestDef;
+use adventurers_quest::snapshot::QuestSnapshot;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::Engine;
+use crate::player::Player;
+use crate::Map;
+
+/// A saved game, stored as RON to match the map files
+///
+/// The map is not saved, only the changes made to it and a hash of the map it
+/// was saved with, so that a save can't be loaded with a different map. The
+/// quest is saved with its state, so that a save can't be loaded with a
+/// different quest
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    /// The player
+    player: Player,
+    /// A hash of the map the game started with, which is missing from saves
+    /// made before it was stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    map_hash: Option<u64>,
+    /// The positions of the blocks removed from the map, e.g. the objects picked up
+    removed_blocks: Vec<(i32, i32)>,
+    /// The state of the quest
+    quest: QuestSnapshot,
+    /// The description of the quest, which is missing from saves made before
+    /// it was stored, which are only checked against the quest's structure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quest_def: Option<QuestDef>,
+}
+
+impl SaveGame {
+    /// Create a save of the current game state
+    ///
+    /// The map delta is found by comparing the current map with the map the game
+    /// started with, and the quest is described by the given definition
+    pub fn new(engine: &Engine, initial_map: &Map, quest: &QuestDef) -> Self {
+        let mut removed_blocks: Vec<(i32, i32)> = initial_map
+            .keys()
+            .filter(|position| !engine.map.contains_key(position))
+            .copied()
+            .collect();
+        removed_blocks.sort();
+
+        Self {
+            player: engine.player.clone(),
+            map_hash: Some(map_hash(initial_map)),
+            removed_blocks,
+            quest: engine.quest.snapshot(),
+            quest_def: Some(quest.clone()),
+        }
+    }
+
+    /// Read a save from a RON file
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or is not a correctly formatted save
+    pub fn read(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read save file {path}: {e}"))?;
+        Ok(ron::from_str(&contents)
+            .map_err(|e| format!("Failed to read save file {path} as RON: {e}"))?)
+    }
+
+    /// Write the save to a RON file
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be written
+    pub fn write(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, contents).map_err(|e| format!("Failed to write save file {path}: {e}"))?;
+        Ok(())
+    }
+
+    /// Restore the saved state onto the game
+    ///
+    /// # Errors
+    ///
+    /// If the save was made with a different map or quest than the given ones,
+    /// or the saved quest state does not match the quest, in which case nothing
+    /// is changed
+    pub fn apply(
+        &self,
+        engine: &mut Engine,
+        initial_map: &Map,
+        quest: &QuestDef,
+    ) -> Result<(), Box<dyn Error>> {
+        if self
+            .map_hash
+            .is_some_and(|hash| hash != map_hash(initial_map))
+        {
+            return Err("The save was made with a different map".into());
+        }
+        if self.quest_def.as_ref().is_some_and(|saved| saved != quest) {
+            return Err("The save was made with a different quest".into());
+        }
+
+        let old_quest = engine.quest.snapshot();
+        if let Err(e) = engine.quest.restore(&self.quest) {
+            engine.quest.restore(&old_quest)?;
+            return Err(format!("The save was made with a different quest. {e}").into());
+        }
+
+        engine.map = initial_map.clone();
+        for position in &self.removed_blocks {
+            engine.map.remove(position);
+        }
+        engine.player = self.player.clone();
+
+        Ok(())
+    }
+}
+
+/// A hash of the blocks on a map, which stays the same between runs of the game
+///
+/// This is the 64-bit FNV-1a hash of the blocks written as RON in order of
+/// position
+fn map_hash(map: &Map) -> u64 {
+    let blocks: BTreeMap<_, _> = map.iter().collect();
+    ron::to_string(&blocks)
+        .expect("blocks can be written as RON")
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use blocks::Block;
+
+    use super::*;
+
+    #[test]
+    fn different_quest_test() {
+        let map: Map = [((0, 0), Block::Grass), ((1, 0), Block::Object('x'))]
+            .into_iter()
+            .collect();
+        let quest = QuestDef::Collect('x', 1);
+        let mut engine = Engine::new(map.clone(), Player::default(), &quest);
+        let save = SaveGame::new(&engine, &map, &quest);
+
+        // Same structure, but a different object to collect
+        let other = QuestDef::Collect('y', 1);
+        assert!(save.apply(&mut engine, &map, &other).is_err());
+        assert!(save.apply(&mut engine, &map, &quest).is_ok());
+
+        // Saves made before the quest was stored are only checked by structure
+        let old_save = SaveGame {
+            quest_def: None,
+            ..save
+        };
+        assert!(old_save.apply(&mut engine, &map, &other).is_ok());
+    }
+
+    #[test]
+    fn different_map_test() {
+        let map: Map = [((0, 0), Block::Grass), ((1, 0), Block::Object('x'))]
+            .into_iter()
+            .collect();
+        let quest = QuestDef::Collect('x', 1);
+        let mut engine = Engine::new(map.clone(), Player::default(), &quest);
+        let save = SaveGame::new(&engine, &map, &quest);
+
+        // Same positions, but a different block
+        let mut other: Map = map.clone();
+        other.insert((0, 0), Block::Sand);
+        assert_eq!(
+            save.apply(&mut engine, &other, &quest)
+                .unwrap_err()
+                .to_string(),
+            "The save was made with a different map"
+        );
+        assert!(save.apply(&mut engine, &map, &quest).is_ok());
+
+        // Saves made before the map was hashed can't be checked
+        let old_save = SaveGame {
+            map_hash: None,
+            ..save
+        };
+        assert!(old_save.apply(&mut engine, &other, &quest).is_ok());
+    }
+}
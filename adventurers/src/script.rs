@@ -0,0 +1,219 @@
+//! # Script module
+//!
+//! Play a script of moves against the game without a terminal
+//!
+//! A script is a text file of moves. Each line is either a single key
+//! (`Up`, `Down`, `Left` or `Right`), or any number of the letters `N`, `E`,
+//! `S` and `W`, e.g. `NNEESSW`. Blank lines and anything after a `#` are ignored.
+
+use crate::engine::Engine;
+use crate::movement::CardinalDirection;
+
+/// The number of moves written on each line by [`format_script`]
+const MOVES_PER_LINE: usize = 40;
+
+/// Parse the moves in a script
+///
+/// # Errors
+///
+/// If a line of the script is not a key or a series of direction letters,
+/// with the line number of the first invalid line
+pub fn parse_script(contents: &str) -> Result<Vec<CardinalDirection>, String> {
+    let mut moves = Vec::new();
+
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+
+        match line.to_lowercase().as_str() {
+            "" => {}
+            "up" => moves.push(CardinalDirection::North),
+            "right" => moves.push(CardinalDirection::East),
+            "down" => moves.push(CardinalDirection::South),
+            "left" => moves.push(CardinalDirection::West),
+            letters => {
+                for letter in letters.chars().filter(|c| !c.is_whitespace()) {
+                    moves.push(match letter {
+                        'n' => CardinalDirection::North,
+                        'e' => CardinalDirection::East,
+                        's' => CardinalDirection::South,
+                        'w' => CardinalDirection::West,
+                        _ => {
+                            return Err(format!(
+                                "Invalid move {:?} on line {} of the script",
+                                line,
+                                line_no + 1
+                            ))
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(moves)
+}
+
+/// Write moves as a script, as direction letters with a fixed number per line
+pub fn format_script(moves: &[CardinalDirection]) -> String {
+    moves
+        .chunks(MOVES_PER_LINE)
+        .map(|line| {
+            line.iter()
+                .map(|card_dir| match card_dir {
+                    CardinalDirection::North => 'N',
+                    CardinalDirection::East => 'E',
+                    CardinalDirection::South => 'S',
+                    CardinalDirection::West => 'W',
+                })
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
+
+/// Play the moves against the game until they run out, or the game ends
+///
+/// # Returns
+///
+/// The number of moves played
+pub fn run_script(engine: &mut Engine, moves: &[CardinalDirection]) -> usize {
+    let mut played = 0;
+
+    for card_dir in moves {
+        if engine.player.won || engine.player.failed || engine.player.is_dead() {
+            break;
+        }
+
+        engine.do_move(*card_dir);
+        played += 1;
+    }
+
+    played
+}
+
+/// Describe the state of the game after a script has been played
+pub fn report(engine: &Engine, played: usize, total: usize) -> String {
+    let position = engine.player.position();
+    let outcome = if engine.player.won {
+        "won"
+    } else if engine.player.failed {
+        "lost (quest failed)"
+    } else if engine.player.is_dead() {
+        "lost (player died)"
+    } else {
+        "not finished"
+    };
+
+    format!(
+        "Moves played: {played} of {total}\nPosition: ({}, {})\nHealth: {}\nGame: {outcome}\nQuest:\n{}",
+        position.x,
+        position.y,
+        engine.player.health(),
+        engine.quest
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use adventurers_quest::definition::QuestDef;
+
+    use super::*;
+    use crate::movement::Coordinate;
+    use crate::player::Player;
+    use crate::Map;
+
+    #[test]
+    fn parse_script_test() {
+        use CardinalDirection::*;
+        assert_eq!(
+            parse_script("NNE s\n# comment\n\nUp\nleft # go left\nW").unwrap(),
+            vec![North, North, East, South, North, West, West]
+        );
+        assert_eq!(
+            parse_script(&format_script(&[North, East, South, West])).unwrap(),
+            vec![North, East, South, West]
+        );
+        assert_eq!(
+            parse_script("NNE\nNorthwest").unwrap_err(),
+            "Invalid move \"Northwest\" on line 2 of the script"
+        );
+    }
+
+    /// The shipped script completes q1 on the shipped map
+    #[test]
+    fn full_game_q1_test() {
+        let engine = play_full_game(
+            include_str!("../../quests/q1.ron"),
+            include_str!("../../scripts/full_game_q1.txt"),
+        );
+
+        assert_eq!(engine.player.position(), Coordinate::new(8, 7));
+        assert_eq!(engine.player.health(), 10);
+        assert_eq!(
+            engine.quest.to_string(),
+            "Repeat 0 times:\n\t[✅] Walk on a Sand block"
+        );
+        assert!(engine.player.won);
+    }
+
+    /// Play a shipped script with a shipped quest on the shipped map
+    fn play_full_game(quest: &str, script: &str) -> Engine {
+        let map: Map =
+            ron::from_str(&fs::read_to_string("../maps/full_game.ron").unwrap()).unwrap();
+        let quest = ron::from_str::<QuestDef>(quest).unwrap();
+        let mut engine = Engine::new(map, Player::default(), &quest);
+
+        let moves = parse_script(script).unwrap();
+        assert_eq!(run_script(&mut engine, &moves), moves.len());
+        engine
+    }
+
+    /// q2 can't be completed on the shipped map, which has only one 'x' object
+    #[test]
+    fn full_game_q2_test() {
+        let engine = play_full_game(
+            include_str!("../../quests/q2.ron"),
+            include_str!("../../scripts/full_game_q2.txt"),
+        );
+
+        assert_eq!(engine.player.position(), Coordinate::new(72, 1));
+        assert_eq!(engine.player.health(), 10);
+        assert_eq!(
+            engine.quest.to_string(),
+            "[ ] You must, in order, complete each of these quests:
+\tRepeat 4 times:
+\t\t[ ] Walk on a 'x' block
+\tRepeat 3 times:
+\t\t[ ] Walk on a 'y' block"
+        );
+        assert!(!engine.player.won);
+    }
+
+    /// The shipped script completes q3 on the shipped map
+    #[test]
+    fn full_game_q3_test() {
+        let engine = play_full_game(
+            include_str!("../../quests/q3.ron"),
+            include_str!("../../scripts/full_game_q3.txt"),
+        );
+
+        assert_eq!(engine.player.position(), Coordinate::new(72, 1));
+        assert_eq!(engine.player.health(), 10);
+        assert_eq!(
+            engine.quest.to_string(),
+            "[✅] You must complete at least 2 of these quests:
+\t[✅] You must, in order, complete each of these quests:
+\t\tRepeat 0 times:
+\t\t\t[✅] Walk on a Sand block
+\t\t[✅] Walk on a 'x' block
+\t[ ] You must, in order, complete each of these quests:
+\t\t[✅] Walk on a 'x' block
+\t\t[ ] Walk on a Grass block
+\tRepeat 0 times:
+\t\t[✅] Walk on exactly 9 blocks of Water in a row"
+        );
+        assert!(engine.player.won);
+    }
+}
@@ -0,0 +1,30 @@
+//! # Serde helpers
+//!
+//! Optional fields of the config and map files are written without `Some`, e.g.
+//! `health: 20` rather than `health: Some(20)`, and are left out when unset
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Read an optional field that is written without `Some`
+///
+/// Use it with `#[serde(deserialize_with = "some")]`, along with a default for
+/// when the field is missing
+pub fn some<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Write an optional field without `Some`, so that [`some`] can read it back
+///
+/// Use it with `#[serde(serialize_with = "unwrap_some")]`, skipping the field
+/// when it is `None`
+pub fn unwrap_some<S: Serializer, T: Serialize>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => value.serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,214 @@
+//! # Solver module
+//!
+//! Search for a sequence of moves that completes a quest on a map
+//!
+//! The search itself is [`adventurers_quest::solver`], which plays moves with
+//! the game's own [`Engine`], restoring the player, the picked up objects and a
+//! snapshot of the quest before each move
+//!
+//! Game ticks are not played, so quests must be completable by moving alone
+
+use std::hash::{Hash, Hasher};
+
+use adventurers_quest::snapshot::QuestSnapshot;
+use adventurers_quest::solver::{self, Playable};
+use adventurers_quest::QuestStatus;
+use blocks::Block;
+
+use crate::engine::Engine;
+use crate::movement::{CardinalDirection, Coordinate};
+use crate::player::Player;
+use crate::{map_bounds, Map};
+
+/// The result of searching for moves that complete a quest
+pub type Solution = solver::Solution<CardinalDirection>;
+
+/// The parts of a [`State`] that affect how the game plays out from it
+type StateKey = ((i32, i32), i32, Vec<(i32, i32)>, QuestSnapshot);
+
+/// The state of the game while searching
+#[derive(Clone)]
+struct State {
+    /// The player, including their position, health and inventory
+    player: Player,
+    /// The positions of the objects that have been picked up
+    picked_up: Vec<(i32, i32)>,
+    /// The state of the quest
+    quest: QuestSnapshot,
+}
+
+impl State {
+    /// The parts of the state that affect how the game plays out from here
+    ///
+    /// The player's inventory is left out, since it follows from the objects
+    /// picked up
+    fn key(&self) -> StateKey {
+        (
+            self.player.position().into(),
+            self.player.health(),
+            self.picked_up.clone(),
+            self.quest.clone(),
+        )
+    }
+}
+
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for State {}
+
+impl Hash for State {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// An [`Engine`] being searched for moves that complete its quest
+struct Search<'a> {
+    /// The engine the moves are played with
+    engine: &'a mut Engine,
+    /// The map before any objects were picked up
+    initial_map: Map,
+    /// The positions of the objects on the initial map
+    objects: Vec<(i32, i32)>,
+    /// The smallest and largest coordinates worth moving to
+    bounds: (Coordinate, Coordinate),
+}
+
+impl Playable for Search<'_> {
+    type State = State;
+    type Move = CardinalDirection;
+
+    fn state(&self) -> State {
+        State {
+            player: self.engine.player.clone(),
+            picked_up: picked_up(&self.engine.map, &self.objects),
+            quest: self.engine.quest.snapshot(),
+        }
+    }
+
+    fn restore(&mut self, state: &State) {
+        for position in &self.objects {
+            if state.picked_up.contains(position) {
+                self.engine.map.remove(position);
+            } else {
+                self.engine
+                    .map
+                    .insert(*position, self.initial_map[position].clone());
+            }
+        }
+        self.engine.player = state.player.clone();
+        self.engine
+            .quest
+            .restore(&state.quest)
+            .expect("snapshots are taken from the same quest");
+    }
+
+    fn moves(&self, state: &State) -> Vec<CardinalDirection> {
+        let (min, max) = self.bounds;
+        CardinalDirection::ALL
+            .into_iter()
+            .filter(|&card_dir| {
+                let next_position = state.player.position() + card_dir;
+                (min.x..=max.x).contains(&next_position.x)
+                    && (min.y..=max.y).contains(&next_position.y)
+            })
+            .collect()
+    }
+
+    fn play(&mut self, card_dir: CardinalDirection) -> QuestStatus {
+        self.engine.do_move(card_dir);
+        let player = &self.engine.player;
+        if player.won {
+            QuestStatus::Complete
+        } else if player.failed || player.is_dead() {
+            QuestStatus::Failed
+        } else {
+            QuestStatus::Ongoing
+        }
+    }
+}
+
+/// Search for the fewest moves that complete the engine's quest, starting
+/// from the engine's current state
+///
+/// The search gives up after exploring `max_states` states. The engine is left
+/// in an unspecified state afterwards
+pub fn solve(engine: &mut Engine, max_states: usize) -> Solution {
+    let initial_map = engine.map.clone();
+    let objects: Vec<(i32, i32)> = initial_map
+        .kinds()
+        .filter(|block| matches!(block, Block::Object(_)))
+        .flat_map(|block| initial_map.positions_of(block))
+        .collect();
+    let bounds = map_bounds(&initial_map, engine.player.position());
+
+    solver::solve(
+        &mut Search {
+            engine,
+            initial_map,
+            objects,
+            bounds,
+        },
+        max_states,
+    )
+}
+
+/// The positions of the objects that are no longer on the map, in order
+fn picked_up(map: &Map, objects: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    objects
+        .iter()
+        .filter(|position| !map.contains_key(position))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::script::run_script;
+    use crate::testing::engine;
+
+    /// The moves found complete q3 when played on a fresh game
+    #[test]
+    fn testing_game_q3_test() {
+        let map: Map =
+            ron::from_str(&fs::read_to_string("../maps/testing_game.ron").unwrap()).unwrap();
+        let quest = include_str!("../../quests/q3.ron");
+
+        let Solution::Solved(moves) = solve(&mut engine(map.clone(), quest), 100_000) else {
+            panic!("q3 should be solvable on the testing map");
+        };
+        let mut engine = engine(map, quest);
+        assert_eq!(run_script(&mut engine, &moves), moves.len());
+        assert!(engine.player.won);
+    }
+
+    /// Collecting the same object twice is impossible, since it disappears
+    #[test]
+    fn impossible_test() {
+        use Block::*;
+        let map: Map = [((1, 2), Grass), ((2, 2), Grass), ((3, 2), Object('x'))]
+            .into_iter()
+            .collect();
+
+        // the object is picked up when leaving it in any direction
+        assert_eq!(
+            solve(&mut engine(map.clone(), "Collect('x', 1)"), 1000),
+            Solution::Solved(vec![CardinalDirection::East, CardinalDirection::North])
+        );
+        assert!(matches!(
+            solve(&mut engine(map.clone(), "Collect('x', 2)"), 1000),
+            Solution::Impossible { .. }
+        ));
+        assert_eq!(
+            solve(&mut engine(map, "Collect('x', 2)"), 10),
+            Solution::GaveUp { states: 10 }
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! # Test helpers
+//!
+//! Helpers shared by the tests of the game's modules
+
+use adventurers_quest::definition::QuestDef;
+use blocks::Block;
+
+use crate::engine::Engine;
+use crate::player::Player;
+use crate::Map;
+
+/// Create a map where each row in `rows` is a row of blocks starting at (0, 0)
+pub fn map(rows: &[&[Block]]) -> Map {
+    rows.iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(move |(x, block)| ((x as i32, y as i32), block.clone()))
+        })
+        .collect()
+}
+
+/// Create an engine with the player at (2, 2) on the given map, playing the
+/// quest described by the RON `quest`
+pub fn engine(map: Map, quest: &str) -> Engine {
+    let quest = ron::from_str::<QuestDef>(quest).unwrap();
+    Engine::new(map, Player::default(), &quest)
+}
@@ -0,0 +1,524 @@
+//! # Tiled module
+//!
+//! Import maps laid out in the [Tiled](https://www.mapeditor.org) editor, from
+//! either its JSON format (`.tmj` or `.json`) or its XML format (`.tmx`)
+//!
+//! Each tile becomes a block, as given by a tileset mapping file. Tiles are
+//! looked up by the IDs Tiled writes in the map file, which are numbered from
+//! the `firstgid` of their tileset, and ID 0 is no tile:
+//!
+//! ```text
+//! (
+//!     tiles: {
+//!         1: Barrier,
+//!         2: Grass,
+//!         3: Sign("Keep out"),
+//!     },
+//!     objects: {
+//!         "key": 'k',
+//!     },
+//! )
+//! ```
+//!
+//! Every tile layer is imported, with later layers drawn over earlier ones.
+//! Objects are drawn over the tiles at the tile they are placed in:
+//!
+//! - Point objects become [`Block::Object`]s of the character they are named,
+//!   or of the character given for their name in the mapping's `objects`
+//! - Text objects become [`Block::Sign`]s with their text
+//! - Named rectangles become regions of the map, covering every tile they touch
+//!
+//! Any other objects are ignored. Only finite maps with tile layers stored as
+//! CSV (the default) are supported
+
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use adventurers_quest::quests::Rect;
+use blocks::Block;
+use roxmltree::Node;
+use serde::Deserialize;
+
+use crate::map_file::{MapFile, Regions};
+use crate::Map;
+
+/// The bits of a tile ID that flip or rotate the tile rather than identify it
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+/// How the tiles and objects of a Tiled map become blocks, as written in a
+/// tileset mapping file
+#[derive(Debug, Default, Deserialize)]
+pub struct TilesetMapping {
+    /// The block of each tile ID
+    pub tiles: HashMap<u32, Block>,
+    /// The character of each point object that isn't named with one character
+    #[serde(default)]
+    pub objects: HashMap<String, char>,
+}
+
+/// A tile layer, with a tile ID for each tile from the top left, row by row
+#[derive(Debug)]
+struct TileLayer {
+    width: usize,
+    tiles: Vec<u32>,
+}
+
+/// The shape of an object on an object layer
+#[derive(Debug)]
+enum Shape {
+    Point,
+    Text(String),
+    Rectangle,
+    /// Ellipses, polygons, tile objects and anything else
+    Other,
+}
+
+/// An object on an object layer, positioned in pixels
+#[derive(Debug)]
+struct Object {
+    id: u32,
+    name: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    shape: Shape,
+}
+
+/// The parts of a Tiled map that are imported, in either format
+#[derive(Debug)]
+struct TiledMap {
+    tile_width: f64,
+    tile_height: f64,
+    layers: Vec<TileLayer>,
+    objects: Vec<Object>,
+}
+
+/// Import a Tiled map file, in the format given by its extension, and a
+/// tileset mapping file
+///
+/// # Errors
+///
+/// If either file cannot be read or is incorrectly formatted, the map uses a
+/// feature that isn't supported, or a tile or object has no block
+pub fn import(map_path: &str, mapping_path: &str) -> Result<MapFile, Box<dyn Error>> {
+    let contents = fs::read_to_string(mapping_path)
+        .map_err(|e| format!("Failed to read tileset mapping file {mapping_path}: {e}"))?;
+    let mapping: TilesetMapping = ron::from_str(&contents)
+        .map_err(|e| format!("Failed to read tileset mapping file {mapping_path} as RON: {e}"))?;
+
+    let contents = fs::read_to_string(map_path)
+        .map_err(|e| format!("Failed to read Tiled map file {map_path}: {e}"))?;
+    let extension = Path::new(map_path).extension().and_then(|e| e.to_str());
+    let tiled = match extension {
+        Some("tmj" | "json") => from_json(&contents),
+        Some("tmx") => from_tmx(&contents),
+        _ => Err(String::from(
+            "Tiled map files must end in .tmj or .json (JSON), or .tmx (XML)",
+        )),
+    }
+    .map_err(|e| format!("Failed to read Tiled map file {map_path}: {e}"))?;
+
+    Ok(to_map_file(&tiled, &mapping)
+        .map_err(|e| format!("Failed to import Tiled map file {map_path}: {e}"))?)
+}
+
+/// The blocks and regions of a Tiled map
+fn to_map_file(tiled: &TiledMap, mapping: &TilesetMapping) -> Result<MapFile, String> {
+    let mut blocks = Map::new();
+    let mut unmapped = BTreeSet::new();
+    for layer in &tiled.layers {
+        for (i, &id) in layer.tiles.iter().enumerate() {
+            let id = id & !FLIP_FLAGS;
+            if id == 0 {
+                continue;
+            }
+            let position = ((i % layer.width) as i32, (i / layer.width) as i32);
+            match mapping.tiles.get(&id) {
+                Some(block) => {
+                    blocks.insert(position, block.clone());
+                }
+                None => {
+                    unmapped.insert(id);
+                }
+            }
+        }
+    }
+    if !unmapped.is_empty() {
+        let ids: Vec<String> = unmapped.iter().map(u32::to_string).collect();
+        return Err(format!(
+            "tile IDs {} are not in the tileset mapping",
+            ids.join(", ")
+        ));
+    }
+
+    // the tile an object's pixel position is in
+    let tile = |x: f64, y: f64| {
+        (
+            (x / tiled.tile_width).floor() as i32,
+            (y / tiled.tile_height).floor() as i32,
+        )
+    };
+    let mut regions = Regions::new();
+    for object in &tiled.objects {
+        let position = tile(object.x, object.y);
+        match &object.shape {
+            Shape::Point => {
+                let mut chars = object.name.chars();
+                let c = match (
+                    mapping.objects.get(&object.name),
+                    chars.next(),
+                    chars.next(),
+                ) {
+                    (Some(&c), _, _) | (None, Some(c), None) => c,
+                    _ => {
+                        return Err(format!(
+                            "point object {} is named \"{}\", which is neither one character \
+                             nor in the tileset mapping's objects",
+                            object.id, object.name
+                        ))
+                    }
+                };
+                blocks.insert(position, Block::Object(c));
+            }
+            Shape::Text(text) => {
+                blocks.insert(position, Block::Sign(text.clone()));
+            }
+            Shape::Rectangle if !object.name.is_empty() => {
+                // the far edges of a rectangle are on the tiles before them
+                let far_edge = |start: f64, size: f64, tile_size: f64| {
+                    ((start + size) / tile_size).ceil() as i32 - 1
+                };
+                let max = (
+                    far_edge(object.x, object.width, tiled.tile_width).max(position.0),
+                    far_edge(object.y, object.height, tiled.tile_height).max(position.1),
+                );
+                regions.insert(object.name.clone(), Rect { min: position, max });
+            }
+            Shape::Rectangle | Shape::Other => {}
+        }
+    }
+
+    Ok(MapFile {
+        map: blocks,
+        regions,
+        ..MapFile::default()
+    })
+}
+
+/// A map in Tiled's JSON format
+#[derive(Debug, Deserialize)]
+struct JsonMap {
+    tilewidth: f64,
+    tileheight: f64,
+    #[serde(default)]
+    infinite: bool,
+    layers: Vec<JsonLayer>,
+}
+
+/// A layer in Tiled's JSON format
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum JsonLayer {
+    #[serde(rename = "tilelayer")]
+    Tiles {
+        width: usize,
+        encoding: Option<String>,
+        data: serde_json::Value,
+    },
+    #[serde(rename = "objectgroup")]
+    Objects { objects: Vec<JsonObject> },
+    #[serde(rename = "group")]
+    Group { layers: Vec<JsonLayer> },
+    #[serde(other)]
+    Other,
+}
+
+/// An object in Tiled's JSON format
+#[derive(Debug, Deserialize)]
+struct JsonObject {
+    id: u32,
+    #[serde(default)]
+    name: String,
+    x: f64,
+    y: f64,
+    #[serde(default)]
+    width: f64,
+    #[serde(default)]
+    height: f64,
+    #[serde(default)]
+    point: bool,
+    text: Option<JsonText>,
+    gid: Option<u32>,
+    #[serde(default)]
+    ellipse: bool,
+    polygon: Option<serde_json::Value>,
+    polyline: Option<serde_json::Value>,
+}
+
+/// The text of a text object in Tiled's JSON format
+#[derive(Debug, Deserialize)]
+struct JsonText {
+    text: String,
+}
+
+/// Read a map in Tiled's JSON format
+fn from_json(contents: &str) -> Result<TiledMap, String> {
+    let json: JsonMap = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    if json.infinite {
+        return Err(String::from("infinite maps are not supported"));
+    }
+
+    let mut tiled = TiledMap {
+        tile_width: json.tilewidth,
+        tile_height: json.tileheight,
+        layers: vec![],
+        objects: vec![],
+    };
+    add_json_layers(&mut tiled, json.layers)?;
+    Ok(tiled)
+}
+
+/// Add the layers in Tiled's JSON format, including those in groups, in order
+fn add_json_layers(tiled: &mut TiledMap, layers: Vec<JsonLayer>) -> Result<(), String> {
+    for layer in layers {
+        match layer {
+            JsonLayer::Tiles {
+                width,
+                encoding,
+                data,
+            } => {
+                if encoding.is_some_and(|encoding| encoding != "csv") {
+                    return Err(unsupported_encoding());
+                }
+                let tiles = serde_json::from_value(data).map_err(|e| e.to_string())?;
+                tiled.layers.push(TileLayer { width, tiles });
+            }
+            JsonLayer::Objects { objects } => {
+                tiled.objects.extend(objects.into_iter().map(|object| {
+                    let shape = if object.point {
+                        Shape::Point
+                    } else if let Some(text) = object.text {
+                        Shape::Text(text.text)
+                    } else if object.gid.is_some()
+                        || object.ellipse
+                        || object.polygon.is_some()
+                        || object.polyline.is_some()
+                    {
+                        Shape::Other
+                    } else {
+                        Shape::Rectangle
+                    };
+                    Object {
+                        id: object.id,
+                        name: object.name,
+                        x: object.x,
+                        y: object.y,
+                        width: object.width,
+                        height: object.height,
+                        shape,
+                    }
+                }));
+            }
+            JsonLayer::Group { layers } => add_json_layers(tiled, layers)?,
+            JsonLayer::Other => {}
+        }
+    }
+    Ok(())
+}
+
+/// Read a map in Tiled's XML format
+fn from_tmx(contents: &str) -> Result<TiledMap, String> {
+    let document = roxmltree::Document::parse(contents).map_err(|e| e.to_string())?;
+    let map = document.root_element();
+    if !map.has_tag_name("map") {
+        return Err(String::from("the document is not a Tiled map"));
+    }
+    if map.attribute("infinite") == Some("1") {
+        return Err(String::from("infinite maps are not supported"));
+    }
+
+    let mut tiled = TiledMap {
+        tile_width: attribute(map, "tilewidth")?,
+        tile_height: attribute(map, "tileheight")?,
+        layers: vec![],
+        objects: vec![],
+    };
+    add_tmx_layers(&mut tiled, map)?;
+    Ok(tiled)
+}
+
+/// Add the layers that are children of the given map or group in Tiled's XML
+/// format, including those in groups, in order
+///
+/// Other elements are skipped, e.g. the collision shapes of a tileset's tiles,
+/// which are also written as object groups
+fn add_tmx_layers(tiled: &mut TiledMap, parent: Node) -> Result<(), String> {
+    for node in parent.children() {
+        match node.tag_name().name() {
+            "layer" => tiled.layers.push(tmx_layer(node)?),
+            "objectgroup" => {
+                for object in node.children().filter(|n| n.has_tag_name("object")) {
+                    tiled.objects.push(tmx_object(object)?);
+                }
+            }
+            "group" => add_tmx_layers(tiled, node)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Read a tile layer in Tiled's XML format
+fn tmx_layer(layer: Node) -> Result<TileLayer, String> {
+    let width = attribute(layer, "width")?;
+    let data = layer
+        .children()
+        .find(|n| n.has_tag_name("data"))
+        .ok_or("a tile layer has no data")?;
+
+    let tiles = match data.attribute("encoding") {
+        Some("csv") => data
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(|id| {
+                id.trim()
+                    .parse()
+                    .map_err(|_| format!("{:?} is not a tile ID", id.trim()))
+            })
+            .collect::<Result<_, _>>()?,
+        // the oldest format, with an element for each tile
+        None => data
+            .children()
+            .filter(|n| n.has_tag_name("tile"))
+            .map(|tile| match tile.attribute("gid") {
+                Some(_) => attribute(tile, "gid"),
+                None => Ok(0),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(unsupported_encoding()),
+    };
+    Ok(TileLayer { width, tiles })
+}
+
+/// Read an object in Tiled's XML format
+fn tmx_object(object: Node) -> Result<Object, String> {
+    let child = |name| object.children().find(|n| n.has_tag_name(name));
+    let shape = if child("point").is_some() {
+        Shape::Point
+    } else if let Some(text) = child("text") {
+        Shape::Text(String::from(text.text().unwrap_or_default()))
+    } else if object.attribute("gid").is_some()
+        || ["ellipse", "polygon", "polyline"]
+            .into_iter()
+            .any(|name| child(name).is_some())
+    {
+        Shape::Other
+    } else {
+        Shape::Rectangle
+    };
+
+    let optional = |name| match object.attribute(name) {
+        Some(_) => attribute(object, name),
+        None => Ok(0.0),
+    };
+    Ok(Object {
+        id: attribute(object, "id")?,
+        name: String::from(object.attribute("name").unwrap_or_default()),
+        x: attribute(object, "x")?,
+        y: attribute(object, "y")?,
+        width: optional("width")?,
+        height: optional("height")?,
+        shape,
+    })
+}
+
+/// Read a number from an attribute of an element in Tiled's XML format
+fn attribute<T: std::str::FromStr>(node: Node, name: &str) -> Result<T, String> {
+    let value = node.attribute(name).ok_or(format!(
+        "a <{}> element has no {name} attribute",
+        node.tag_name().name()
+    ))?;
+    value.parse().map_err(|_| {
+        format!(
+            "the {name} attribute of a <{}> element is {value:?}, which is not a number",
+            node.tag_name().name()
+        )
+    })
+}
+
+/// The error for tile layers stored in a format other than CSV
+fn unsupported_encoding() -> String {
+    String::from(
+        "only tile layers stored as CSV are supported, \
+         so set the tile layer format to CSV in Tiled's map properties",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_map;
+
+    /// The blocks and regions of the fixtures in `maps/tiled`
+    fn expected() -> MapFile {
+        let header = r#"(
+            legend: {'0': Sign("Welcome to the island"), 'k': Object('k'), 'x': Object('x')},
+            regions: {"beach": (min: (3, 1), max: (4, 2))},
+        )"#;
+        grid_map::from_str(&format!("{header}\n---\n######\n#x.0:#\n#.k~:#\n######")).unwrap()
+    }
+
+    #[test]
+    fn import_test() {
+        let mapping = "../maps/tiled/island_tiles.ron";
+        for map in ["../maps/tiled/island.tmj", "../maps/tiled/island.tmx"] {
+            assert_eq!(import(map, mapping).unwrap(), expected(), "{map}");
+        }
+    }
+
+    #[test]
+    fn unmapped_test() {
+        let tiled = from_json(include_str!("../../maps/tiled/island.tmj")).unwrap();
+        let mapping = TilesetMapping {
+            tiles: HashMap::from([(1, Block::Barrier), (2, Block::Grass)]),
+            objects: HashMap::new(),
+        };
+        assert_eq!(
+            to_map_file(&tiled, &mapping).unwrap_err(),
+            "tile IDs 3, 4 are not in the tileset mapping"
+        );
+
+        let tiled = from_tmx(
+            r#"<map tilewidth="16" tileheight="16">
+                <layer width="2"><data encoding="base64">AQAAAA==</data></layer>
+            </map>"#,
+        );
+        assert_eq!(tiled.unwrap_err(), unsupported_encoding());
+    }
+
+    /// Collision shapes in an embedded tileset are not objects of the map
+    #[test]
+    fn tileset_objects_test() {
+        let tiled = from_tmx(
+            r#"<map tilewidth="16" tileheight="16">
+                <tileset firstgid="1">
+                    <tile id="0">
+                        <objectgroup><object id="1" x="0" y="0" width="16" height="16"/></objectgroup>
+                    </tile>
+                </tileset>
+                <group>
+                    <layer width="1"><data encoding="csv">1</data></layer>
+                    <objectgroup><object id="2" name="spawn" x="8" y="8"><point/></object></objectgroup>
+                </group>
+            </map>"#,
+        )
+        .unwrap();
+        assert_eq!(tiled.layers.len(), 1);
+        assert_eq!(tiled.objects.len(), 1);
+        assert_eq!(tiled.objects[0].id, 2);
+    }
+}
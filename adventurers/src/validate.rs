@@ -0,0 +1,590 @@
+//! # Validate module
+//!
+//! Check a map file (and optionally a quest) for problems before playing it
+//!
+//! Errors make the map unplayable, while warnings point out things that are
+//! likely to be mistakes, e.g. parts of the map the player can never reach
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt::Display;
+
+use adventurers_quest::definition::QuestDef;
+use blocks::Block;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::config::PlayerConfig;
+use crate::map_file::{MapFile, MapFormat, Regions};
+use crate::movement::{CardinalDirection, Coordinate};
+use crate::{grid_map, map_bounds, Map};
+
+/// How serious a problem found in a map is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The map cannot be played as intended
+    Error,
+    /// The map can be played, but is probably not as intended
+    Warning,
+}
+
+/// A problem found in a map
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// A description of the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "error: {}", self.message),
+            Severity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+/// The entries of a map file in the order they were written, keeping any
+/// duplicate coordinates that would be lost when reading it as a [`Map`]
+#[derive(Default)]
+struct MapEntries(Vec<((i32, i32), Block)>);
+
+/// The entries of a map file in the extended format, ignoring everything else
+#[derive(Deserialize)]
+struct ExtendedMapEntries {
+    blocks: MapEntries,
+}
+
+impl<'de> Deserialize<'de> for MapEntries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = MapEntries;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map from coordinates to blocks")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<MapEntries, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = access.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(MapEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+/// Check the contents of a map file in the given format, and that the quest can be found on it
+///
+/// The player starts with the given settings, unless the map header overrides them
+///
+/// # Returns
+///
+/// The problems found, which is empty if the map is valid
+pub fn validate(
+    contents: &str,
+    format: MapFormat,
+    player: &PlayerConfig,
+    quest: Option<&QuestDef>,
+) -> Vec<Diagnostic> {
+    let map_file = match format {
+        MapFormat::Ron => MapFile::from_str(contents).map_err(|e| match e.code {
+            // blocks are the only enum in a map file
+            ron::Error::NoSuchEnumVariant {
+                expected, found, ..
+            } => format!(
+                "unknown block `{found}` at line {}, column {}, the blocks are {}",
+                e.position.line,
+                e.position.col,
+                expected.join(", ")
+            ),
+            code => format!(
+                "line {}, column {}: {code}",
+                e.position.line, e.position.col
+            ),
+        }),
+        MapFormat::Grid => grid_map::from_str(contents).map_err(|e| e.to_string()),
+    };
+    let map_file = match map_file {
+        Ok(map_file) => map_file,
+        Err(message) => return vec![Diagnostic::error(message)],
+    };
+
+    let mut diagnostics = Vec::new();
+
+    // a grid can't give a coordinate more than one block
+    if format == MapFormat::Ron {
+        let mut counts: BTreeMap<(i32, i32), usize> = BTreeMap::new();
+        for (position, _) in map_entries(contents).0 {
+            *counts.entry(position).or_default() += 1;
+        }
+        for ((x, y), count) in counts.into_iter().filter(|&(_, count)| count > 1) {
+            diagnostics.push(Diagnostic::error(format!(
+                "({x}, {y}) is given a block {count} times"
+            )));
+        }
+    }
+
+    let player = player.with(&map_file.player);
+    diagnostics.extend(validate_map(
+        &map_file.map,
+        &map_file.regions,
+        &player,
+        quest,
+    ));
+
+    diagnostics
+}
+
+/// Read the entries of the blocks of a map file in either RON format, which is
+/// empty if they can't be read
+fn map_entries(contents: &str) -> MapEntries {
+    ron::from_str(contents)
+        .or_else(|_| ron::from_str(contents).map(|extended: ExtendedMapEntries| extended.blocks))
+        .unwrap_or_default()
+}
+
+/// Check that the map and its regions can be played by the player from their
+/// spawn point, and that the quest can be found on it
+///
+/// # Returns
+///
+/// The problems found, which is empty if the map is valid
+pub fn validate_map(
+    map: &Map,
+    regions: &Regions,
+    player: &PlayerConfig,
+    quest: Option<&QuestDef>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if let Err(e) = player.check() {
+        diagnostics.push(Diagnostic::error(e));
+        return diagnostics;
+    }
+
+    let spawn = (player.spawn.x, player.spawn.y);
+    match map.get(&spawn) {
+        Some(block) if block.blocks_movement() => diagnostics.push(Diagnostic::error(format!(
+            "The spawn point {spawn:?} is on a {block} block"
+        ))),
+        Some(block) if block.damage() > 0 => diagnostics.push(Diagnostic::error(format!(
+            "The spawn point {spawn:?} is on a {block} block, which hurts the player"
+        ))),
+        Some(_) => {}
+        None => diagnostics.push(Diagnostic::warning(format!(
+            "The spawn point {spawn:?} has no block"
+        ))),
+    }
+
+    let reachable = reachable(map, player);
+    for region in unreachable_regions(map, &reachable) {
+        let (x, y) = region[0];
+        let blocks = match region.len() {
+            1 => String::from("1 block"),
+            count => format!("{count} blocks"),
+        };
+        diagnostics.push(Diagnostic::warning(format!(
+            "{blocks} starting at ({x}, {y}) can't be reached from the spawn point"
+        )));
+    }
+
+    for (name, rect) in regions {
+        if rect.min.0 > rect.max.0 || rect.min.1 > rect.max.1 {
+            diagnostics.push(Diagnostic::error(format!(
+                "The region \"{name}\" is empty, since its min corner {:?} is beyond its max corner {:?}",
+                rect.min, rect.max
+            )));
+        } else if !reachable.iter().any(|&position| rect.contains(position)) {
+            diagnostics.push(Diagnostic::warning(format!(
+                "The region \"{name}\" can't be reached from the spawn point"
+            )));
+        }
+    }
+
+    if let Some(quest) = quest {
+        for problem in quest.impossible() {
+            diagnostics.push(Diagnostic::error(format!(
+                "The quest can never be completed: {problem}"
+            )));
+        }
+        for name in undefined_regions(regions, quest) {
+            diagnostics.push(Diagnostic::error(format!(
+                "The quest needs a region called \"{name}\", but the map doesn't define one"
+            )));
+        }
+        for block in unreachable_targets(map, &reachable, quest) {
+            diagnostics.push(Diagnostic::error(format!(
+                "The quest needs a {block} block, but the player can't reach one"
+            )));
+        }
+    }
+
+    diagnostics
+}
+
+/// Find the blocks the quest needs that the player can't reach from their spawn
+/// point
+pub fn missing_quest_blocks(map: &Map, player: &PlayerConfig, quest: &QuestDef) -> Vec<Block> {
+    unreachable_targets(map, &reachable(map, player), quest)
+}
+
+/// Find the names of the regions the quest refers to that the map doesn't define
+pub fn undefined_regions<'a>(regions: &Regions, quest: &'a QuestDef) -> Vec<&'a str> {
+    quest
+        .region_names()
+        .into_iter()
+        .filter(|name| !regions.contains_key(*name))
+        .collect()
+}
+
+/// Find the blocks the quest needs that are not at any of the reachable positions
+fn unreachable_targets(
+    map: &Map,
+    reachable: &BTreeSet<(i32, i32)>,
+    quest: &QuestDef,
+) -> Vec<Block> {
+    let available = |block: &Block| {
+        map.positions_of(block)
+            .any(|position| reachable.contains(&position))
+    };
+    quest.missing_blocks(&available)
+}
+
+/// Find the positions the player can reach from their spawn point without
+/// drowning
+///
+/// The search is limited to the [`map_bounds`]
+fn reachable(map: &Map, player: &PlayerConfig) -> BTreeSet<(i32, i32)> {
+    let start = player.spawn;
+    let (min, max) = map_bounds(map, start);
+
+    // the least damage taken since last being on a safe block, for each position
+    let mut damage_taken: HashMap<(i32, i32), i32> = HashMap::new();
+    let mut queue = VecDeque::new();
+    damage_taken.insert(start.into(), 0);
+    queue.push_back((start, 0));
+
+    while let Some((position, damage)) = queue.pop_front() {
+        for card_dir in CardinalDirection::ALL {
+            let next = position + card_dir;
+            if !(min.x..=max.x).contains(&next.x) || !(min.y..=max.y).contains(&next.y) {
+                continue;
+            }
+
+            let block = map.get(&next.into());
+            if block.is_some_and(Block::blocks_movement) {
+                continue;
+            }
+            let next_damage = match block.map_or(0, Block::damage) {
+                0 => 0,
+                block_damage => damage + block_damage,
+            };
+            if next_damage >= player.health {
+                continue;
+            }
+
+            let best = damage_taken.entry(next.into()).or_insert(i32::MAX);
+            if next_damage < *best {
+                *best = next_damage;
+                queue.push_back((next, next_damage));
+            }
+        }
+    }
+
+    damage_taken.into_keys().collect()
+}
+
+/// Group the blocks of the map that can be walked on but not reached into
+/// connected regions
+///
+/// Each region is sorted, and the regions are sorted by their first position
+fn unreachable_regions(map: &Map, reachable: &BTreeSet<(i32, i32)>) -> Vec<Vec<(i32, i32)>> {
+    let mut unreachable: BTreeSet<(i32, i32)> = map
+        .iter()
+        .filter(|(position, block)| !block.blocks_movement() && !reachable.contains(position))
+        .map(|(&position, _)| position)
+        .collect();
+
+    let mut regions = Vec::new();
+    while let Some(start) = unreachable.pop_first() {
+        let mut region = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(position) = queue.pop_front() {
+            for card_dir in CardinalDirection::ALL {
+                let next = (Coordinate::new(position.0, position.1) + card_dir).into();
+                if unreachable.remove(&next) {
+                    region.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        region.sort_unstable();
+        regions.push(region);
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::map;
+
+    /// Validate a map where each row in `rows` is a row of blocks starting at (0, 0)
+    fn validate_rows(rows: &[&[Block]], quest: Option<&str>) -> Vec<Diagnostic> {
+        let quest = quest.map(|quest| ron::from_str::<QuestDef>(quest).unwrap());
+        validate(
+            &ron::to_string(&map(rows)).unwrap(),
+            MapFormat::Ron,
+            &PlayerConfig::default(),
+            quest.as_ref(),
+        )
+    }
+
+    #[test]
+    fn valid_map_test() {
+        use Block::*;
+        let rows: [&[Block]; 3] = [
+            &[Grass, Grass, Grass, Grass],
+            &[Grass, Sand, Water, Grass],
+            &[Grass, Grass, Grass, Grass],
+        ];
+        assert_eq!(validate_rows(&rows, Some("Walk(Sand)")), vec![]);
+    }
+
+    #[test]
+    fn syntax_error_test() {
+        let diagnostics = validate(
+            "{\n    (0, 0): Grass,\n    (0, 1): Lava,\n}",
+            MapFormat::Ron,
+            &PlayerConfig::default(),
+            None,
+        );
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(String::from(
+                "unknown block `Lava` at line 3, column 17, the blocks are Barrier, \
+                 Cinderblock, Flowerbush, Grass, Object, Rock, Sand, Sign, Water"
+            ))]
+        );
+
+        let diagnostics = validate(
+            "{\n    (0, 0): Grass,\n    (0, 1) Sand,\n}",
+            MapFormat::Ron,
+            &PlayerConfig::default(),
+            None,
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.starts_with("line 3, column "));
+
+        let diagnostics = validate("...\n.L.", MapFormat::Grid, &PlayerConfig::default(), None);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(String::from(
+                "line 2, column 2: 'L' is not in the legend"
+            ))]
+        );
+    }
+
+    #[test]
+    fn duplicate_test() {
+        let diagnostics = validate(
+            "{(0, 0): Grass, (1, 0): Sand, (0, 0): Water, (0, 0): Rock, (2, 2): Grass}",
+            MapFormat::Ron,
+            &PlayerConfig::default(),
+            None,
+        );
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(String::from(
+                "(0, 0) is given a block 3 times"
+            ))]
+        );
+
+        let diagnostics = validate(
+            "(title: \"Twice\", blocks: {(2, 2): Grass, (2, 2): Sand})",
+            MapFormat::Ron,
+            &PlayerConfig::default(),
+            None,
+        );
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(String::from(
+                "(2, 2) is given a block 2 times"
+            ))]
+        );
+    }
+
+    #[test]
+    fn spawn_test() {
+        use Block::*;
+        let rows: [&[Block]; 3] = [
+            &[Grass, Grass, Grass],
+            &[Grass, Grass, Grass],
+            &[Grass, Grass, Water],
+        ];
+        assert_eq!(
+            validate_rows(&rows, None),
+            vec![Diagnostic::error(String::from(
+                "The spawn point (2, 2) is on a Water block, which hurts the player"
+            ))]
+        );
+    }
+
+    #[test]
+    fn unreachable_and_quest_test() {
+        use Block::*;
+        let rows: [&[Block]; 4] = [
+            &[Barrier, Barrier, Barrier, Barrier, Barrier, Barrier],
+            &[Barrier, Grass, Grass, Barrier, Sand, Barrier],
+            &[Barrier, Grass, Grass, Barrier, Sand, Barrier],
+            &[Barrier, Barrier, Barrier, Barrier, Barrier, Barrier],
+        ];
+        assert_eq!(
+            validate_rows(&rows, Some("Any([Walk(Sand), Collect('x', 1)])")),
+            vec![
+                Diagnostic::warning(String::from(
+                    "2 blocks starting at (4, 1) can't be reached from the spawn point"
+                )),
+                Diagnostic::error(String::from(
+                    "The quest needs a Sand block, but the player can't reach one"
+                )),
+                Diagnostic::error(String::from(
+                    "The quest needs a 'x' block, but the player can't reach one"
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn impossible_quest_test() {
+        use Block::*;
+        let rows: [&[Block]; 3] = [
+            &[Grass, Grass, Grass],
+            &[Grass, Grass, Grass],
+            &[Grass, Grass, Sand],
+        ];
+        assert_eq!(
+            validate_rows(&rows, Some("AtLeast(3, [Walk(Sand), Walk(Grass)])")),
+            vec![Diagnostic::error(String::from(
+                "The quest can never be completed: AtLeast(3, ..) has only 2 quests, so 3 can never be completed"
+            ))]
+        );
+    }
+
+    #[test]
+    fn deep_water_test() {
+        use Block::*;
+        let health = PlayerConfig::default().health as usize;
+        let row = [
+            vec![Barrier, Grass, Grass],
+            vec![Water; health],
+            vec![Sand, Barrier],
+        ]
+        .concat();
+        let wall = vec![Barrier; health + 5];
+        let rows = [&wall, &row, &row, &row, &wall].map(Vec::as_slice);
+        let diagnostics = validate_rows(&rows, Some("Walk(Sand)"));
+        assert_eq!(
+            diagnostics.last(),
+            Some(&Diagnostic::error(String::from(
+                "The quest needs a Sand block, but the player can't reach one"
+            )))
+        );
+    }
+
+    /// The map header can give the player enough health to cross deep water
+    #[test]
+    fn map_header_test() {
+        // a Sand block in the middle of a pond two blocks deep
+        let blocks: Vec<String> = (0..5)
+            .flat_map(|y| (0..5).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let block = if (x, y) == (2, 2) { "Sand" } else { "Water" };
+                format!("({x}, {y}): {block}")
+            })
+            .collect();
+        let contents = format!(
+            "(player: (spawn: (-1, 2), health: 3), blocks: {{(-1, 2): Grass, {}}})",
+            blocks.join(", ")
+        );
+        let quest = ron::from_str::<QuestDef>("Walk(Sand)").unwrap();
+        assert_eq!(
+            validate(
+                &contents,
+                MapFormat::Ron,
+                &PlayerConfig::default(),
+                Some(&quest)
+            ),
+            vec![]
+        );
+
+        let player = PlayerConfig {
+            health: 2,
+            ..PlayerConfig::default()
+        };
+        let contents = contents.replace("health: 3", "");
+        assert_eq!(
+            validate(&contents, MapFormat::Ron, &player, Some(&quest)),
+            vec![
+                Diagnostic::warning(String::from(
+                    "9 blocks starting at (1, 1) can't be reached from the spawn point"
+                )),
+                Diagnostic::error(String::from(
+                    "The quest needs a Sand block, but the player can't reach one"
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn regions_test() {
+        let contents = r#"(
+            blocks: {(1, 2): Grass, (2, 2): Grass, (3, 2): Barrier, (4, 2): Grass},
+            regions: {
+                "home": (min: (0, 0), max: (2, 2)),
+                "behind the wall": (min: (10, 2), max: (10, 2)),
+                "nowhere": (min: (1, 1), max: (0, 1)),
+            },
+        )"#;
+        let quest =
+            ron::from_str::<QuestDef>(r#"All([Region(Named("home")), Region(Named("shop"))])"#)
+                .unwrap();
+        assert_eq!(
+            validate(contents, MapFormat::Ron, &PlayerConfig::default(), Some(&quest)),
+            vec![
+                Diagnostic::warning(String::from(
+                    "The region \"behind the wall\" can't be reached from the spawn point"
+                )),
+                Diagnostic::error(String::from(
+                    "The region \"nowhere\" is empty, since its min corner (1, 1) is beyond its max corner (0, 1)"
+                )),
+                Diagnostic::error(String::from(
+                    "The quest needs a region called \"shop\", but the map doesn't define one"
+                )),
+            ]
+        );
+    }
+}
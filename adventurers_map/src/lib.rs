@@ -0,0 +1,422 @@
+//! Game map crate
+//!
+//! A [`Map`] is the blocks of the game world, keyed by their coordinates. It
+//! keeps track of its bounding box and where each kind of block is, so these don't need to be found by looking at
+//! every block:
+//!
+//! ```
+//! # use adventurers_map::{Bounds, Map};
+//! # use blocks::Block;
+//! let mut map = Map::from([((0, 0), Block::Grass), ((3, 1), Block::Sand)]);
+//! map.insert((1, 4), Block::Sand);
+//!
+//! assert_eq!(map.bounds(), Some(Bounds { min: (0, 0), max: (3, 4) }));
+//! let sand: Vec<_> = map.positions_of(&Block::Sand).collect();
+//! assert_eq!(sand, [(1, 4), (3, 1)]);
+//! ```
+//!
+//! Maps are (de)serialized as a map from coordinates to blocks, which is how
+//! map files have always been written, so a map reads back as the same map.
+//! Anything else about a map, like its title or where the player starts, is
+//! kept by whatever stores the map, e.g. a map file's header
+
+#![warn(missing_docs)]
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Debug;
+use std::ops::Index;
+
+use blocks::Block;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The coordinate of a block on a map
+pub type Position = (i32, i32);
+
+/// The offsets of the neighbours of a position, going clockwise from north
+const NEIGHBOURS: [Position; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The smallest rectangle containing a set of positions, including its corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Bounds {
+    /// The corner with the smallest coordinates
+    pub min: Position,
+    /// The corner with the largest coordinates
+    pub max: Position,
+}
+
+impl Bounds {
+    /// The bounds containing just the given position
+    pub fn at(position: Position) -> Self {
+        Self {
+            min: position,
+            max: position,
+        }
+    }
+
+    /// Whether the position is within the bounds
+    pub fn contains(&self, (x, y): Position) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    /// The number of columns within the bounds
+    pub fn width(&self) -> i32 {
+        self.max.0 - self.min.0 + 1
+    }
+
+    /// The number of rows within the bounds
+    pub fn height(&self) -> i32 {
+        self.max.1 - self.min.1 + 1
+    }
+
+    /// The smallest bounds containing these bounds and the given position
+    pub fn including(self, (x, y): Position) -> Self {
+        Self {
+            min: (self.min.0.min(x), self.min.1.min(y)),
+            max: (self.max.0.max(x), self.max.1.max(y)),
+        }
+    }
+
+    /// These bounds with a margin of the given number of blocks added on
+    /// each side
+    pub fn grow(self, margin: i32) -> Self {
+        Self {
+            min: (self.min.0 - margin, self.min.1 - margin),
+            max: (self.max.0 + margin, self.max.1 + margin),
+        }
+    }
+}
+
+/// The blocks of the game world
+///
+/// Positions without a block are empty space, which the player can walk on
+#[derive(Clone, Default)]
+pub struct Map {
+    /// The block at each position
+    blocks: HashMap<Position, Block>,
+    /// The positions of each kind of block on the map
+    index: HashMap<Block, BTreeSet<Position>>,
+    /// The bounds of the blocks, or `None` if there are none
+    bounds: Option<Bounds>,
+}
+
+impl Map {
+    /// Create a map without any blocks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of blocks on the map
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Whether the map has no blocks
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// The block at the given position, if there is one
+    pub fn get(&self, position: &Position) -> Option<&Block> {
+        self.blocks.get(position)
+    }
+
+    /// Whether there is a block at the given position
+    pub fn contains_key(&self, position: &Position) -> bool {
+        self.blocks.contains_key(position)
+    }
+
+    /// Put a block at the given position
+    ///
+    /// # Returns
+    ///
+    /// The block that was replaced, if there was one
+    pub fn insert(&mut self, position: Position, block: Block) -> Option<Block> {
+        let replaced = self.blocks.insert(position, block.clone());
+        if let Some(replaced) = &replaced {
+            self.unindex(position, replaced);
+        }
+        self.index.entry(block).or_default().insert(position);
+
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.including(position),
+            None => Bounds::at(position),
+        });
+        replaced
+    }
+
+    /// Take the block at the given position off the map, leaving empty space
+    ///
+    /// # Returns
+    ///
+    /// The block that was removed, if there was one
+    pub fn remove(&mut self, position: &Position) -> Option<Block> {
+        let removed = self.blocks.remove(position)?;
+        self.unindex(*position, &removed);
+
+        // the bounds only shrink if the block was on their edge
+        if let Some(Bounds { min, max }) = self.bounds {
+            if [min.0, max.0].contains(&position.0) || [min.1, max.1].contains(&position.1) {
+                self.bounds = self.find_bounds();
+            }
+        }
+        Some(removed)
+    }
+
+    /// The blocks of the map and their positions, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&Position, &Block)> {
+        self.blocks.iter()
+    }
+
+    /// The positions of the blocks of the map, in no particular order
+    pub fn keys(&self) -> impl Iterator<Item = &Position> {
+        self.blocks.keys()
+    }
+
+    /// The blocks of the map, in no particular order
+    pub fn values(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.values()
+    }
+
+    /// The smallest bounds containing every block of the map, or `None` if
+    /// there are no blocks
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// The positions of every copy of the block on the map, in order
+    pub fn positions_of(&self, block: &Block) -> impl Iterator<Item = Position> + '_ {
+        self.index.get(block).into_iter().flatten().copied()
+    }
+
+    /// The number of copies of the block on the map
+    pub fn count(&self, block: &Block) -> usize {
+        self.index.get(block).map_or(0, BTreeSet::len)
+    }
+
+    /// The different blocks on the map, in no particular order
+    pub fn kinds(&self) -> impl Iterator<Item = &Block> {
+        self.index.keys()
+    }
+
+    /// The blocks next to the given position, going clockwise from north
+    ///
+    /// Neighbours that are empty space are skipped
+    pub fn neighbours(&self, (x, y): Position) -> impl Iterator<Item = (Position, &Block)> {
+        NEIGHBOURS.into_iter().filter_map(move |(dx, dy)| {
+            let position = (x + dx, y + dy);
+            self.blocks.get(&position).map(|block| (position, block))
+        })
+    }
+
+    /// Remove the position from the index of the block that was there
+    fn unindex(&mut self, position: Position, block: &Block) {
+        if let Some(positions) = self.index.get_mut(block) {
+            positions.remove(&position);
+            if positions.is_empty() {
+                self.index.remove(block);
+            }
+        }
+    }
+
+    /// Find the bounds of the blocks by looking at every block
+    fn find_bounds(&self) -> Option<Bounds> {
+        let mut positions = self.blocks.keys().copied();
+        let first = positions.next()?;
+        Some(positions.fold(Bounds::at(first), Bounds::including))
+    }
+}
+
+/// Maps are equal if they have the same blocks
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.blocks == other.blocks
+    }
+}
+
+impl Eq for Map {}
+
+impl Debug for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Map").field("blocks", &self.blocks).finish()
+    }
+}
+
+impl Index<&Position> for Map {
+    type Output = Block;
+
+    fn index(&self, position: &Position) -> &Block {
+        &self.blocks[position]
+    }
+}
+
+impl Extend<(Position, Block)> for Map {
+    fn extend<I: IntoIterator<Item = (Position, Block)>>(&mut self, blocks: I) {
+        for (position, block) in blocks {
+            self.insert(position, block);
+        }
+    }
+}
+
+/// Later blocks replace earlier blocks at the same position
+impl FromIterator<(Position, Block)> for Map {
+    fn from_iter<I: IntoIterator<Item = (Position, Block)>>(blocks: I) -> Self {
+        let mut map = Map::new();
+        map.extend(blocks);
+        map
+    }
+}
+
+impl<const N: usize> From<[(Position, Block); N]> for Map {
+    fn from(blocks: [(Position, Block); N]) -> Self {
+        blocks.into_iter().collect()
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (Position, Block);
+    type IntoIter = std::collections::hash_map::IntoIter<Position, Block>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.blocks.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a Position, &'a Block);
+    type IntoIter = std::collections::hash_map::Iter<'a, Position, Block>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.blocks.iter()
+    }
+}
+
+/// The blocks are written in order of their positions, so the same map is
+/// always written the same way
+impl Serialize for Map {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.blocks
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Map {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::<Position, Block>::deserialize(deserializer)
+            .map(|blocks| blocks.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_test() {
+        let mut map = Map::from([
+            ((0, 0), Block::Sand),
+            ((1, 0), Block::Sand),
+            ((2, 0), Block::Object('x')),
+        ]);
+        assert_eq!(map.count(&Block::Sand), 2);
+        assert_eq!(map.count(&Block::Water), 0);
+
+        // replacing a block moves its position to the new block's index
+        assert_eq!(map.insert((0, 0), Block::Water), Some(Block::Sand));
+        assert_eq!(map.positions_of(&Block::Sand).collect::<Vec<_>>(), [(1, 0)]);
+        assert_eq!(
+            map.positions_of(&Block::Water).collect::<Vec<_>>(),
+            [(0, 0)]
+        );
+
+        assert_eq!(map.remove(&(2, 0)), Some(Block::Object('x')));
+        assert_eq!(map.remove(&(2, 0)), None);
+        assert_eq!(map.positions_of(&Block::Object('x')).count(), 0);
+        assert_eq!(map.kinds().count(), 2);
+
+        // replacing a block with itself keeps it in the index
+        map.insert((1, 0), Block::Sand);
+        assert_eq!(map.count(&Block::Sand), 1);
+    }
+
+    #[test]
+    fn bounds_test() {
+        let mut map = Map::new();
+        assert_eq!(map.bounds(), None);
+
+        map.extend([
+            ((0, 0), Block::Grass),
+            ((-2, 3), Block::Grass),
+            ((4, 1), Block::Grass),
+        ]);
+        let bounds = map.bounds().unwrap();
+        assert_eq!(
+            bounds,
+            Bounds {
+                min: (-2, 0),
+                max: (4, 3)
+            }
+        );
+        assert_eq!((bounds.width(), bounds.height()), (7, 4));
+        assert!(bounds.contains((0, 2)));
+        assert!(!bounds.grow(1).contains((6, 0)));
+
+        // removing a block inside the bounds keeps them, and on the edge shrinks them
+        map.insert((1, 1), Block::Sand);
+        map.remove(&(1, 1));
+        assert_eq!(map.bounds(), Some(bounds));
+        map.remove(&(-2, 3));
+        assert_eq!(
+            map.bounds(),
+            Some(Bounds {
+                min: (0, 0),
+                max: (4, 1)
+            })
+        );
+        map.remove(&(0, 0));
+        map.remove(&(4, 1));
+        assert_eq!(map.bounds(), None);
+    }
+
+    #[test]
+    fn neighbours_test() {
+        let map = Map::from([
+            ((1, 0), Block::Rock),
+            ((0, 1), Block::Water),
+            ((2, 1), Block::Sand),
+            ((1, 1), Block::Grass),
+            ((2, 2), Block::Grass),
+        ]);
+        assert_eq!(
+            map.neighbours((1, 1)).collect::<Vec<_>>(),
+            [
+                ((1, 0), &Block::Rock),
+                ((2, 1), &Block::Sand),
+                ((0, 1), &Block::Water)
+            ]
+        );
+    }
+
+    #[test]
+    fn serde_test() {
+        // maps are read and written as they always have been
+        let map: Map = ron::from_str("{(1, 0): Sand, (0, 0): Sign(\"Hi\")}").unwrap();
+        assert_eq!(
+            map,
+            Map::from([
+                ((0, 0), Block::Sign(String::from("Hi"))),
+                ((1, 0), Block::Sand)
+            ])
+        );
+        assert_eq!(map.count(&Block::Sand), 1);
+        assert_eq!(
+            ron::to_string(&map).unwrap(),
+            "{(0,0):Sign(\"Hi\"),(1,0):Sand}"
+        );
+        assert_eq!(
+            ron::from_str::<Map>(&ron::to_string(&map).unwrap()).unwrap(),
+            map
+        );
+    }
+}
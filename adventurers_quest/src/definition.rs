@@ -0,0 +1,258 @@
+//! # Quest definitions
+//!
+//! A quest definition is a plain data description of a quest tree. It can be
+//! deserialized from a file (e.g. RON) and then built into a playable [`Quest`].
+//!
+//! For example, the following RON describes a quest where 5 sand blocks must be
+//! walked on before an `'x'` object is collected:
+//!
+//! ```text
+//! Then(Repeat(Walk(Sand), 5), Walk(Object('x')))
+//! ```
+//!
+//! Time limits are given in ticks or moves, e.g. walking on 3 sand blocks within
+//! 20 moves, starting again if the moves run out:
+//!
+//! ```text
+//! Within(Repeat(Walk(Sand), 3), 20, Moves, Reset)
+//! ```
+
+use blocks::Block;
+use serde::{Deserialize, Serialize};
+
+use crate::quests::combinators::{
+    OnTimeout, QuestAll, QuestAny, QuestAtLeast, QuestMajority, QuestRepeat, QuestSequence,
+    QuestThen, QuestWithin, TimeUnit,
+};
+use crate::quests::{
+    AvoidBlockQuest, CollectQuest, Holdings, ReachQuest, Region, RegionQuest, WalkQuest,
+    WalkRepeatQuest,
+};
+use crate::{Event, Quest};
+
+/// The description of a quest tree
+///
+/// Each variant mirrors one of the primitive or combinator quests
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QuestDef {
+    /// A [`WalkQuest`] on the given block
+    Walk(Block),
+    /// A [`WalkRepeatQuest`] on the given block, the given number of times in a row
+    WalkRepeat(Block, u32),
+    /// An [`AvoidBlockQuest`] walking the given number of blocks without touching the given block
+    Avoid(Block, u32),
+    /// A [`CollectQuest`] holding the given number of the given object
+    Collect(char, u32),
+    /// A [`ReachQuest`] moving onto the given coordinate
+    Reach(i32, i32),
+    /// A [`RegionQuest`] moving into the given region
+    Region(Region),
+    /// A [`QuestThen`] completing the first quest and then the second
+    Then(Box<QuestDef>, Box<QuestDef>),
+    /// A [`QuestRepeat`] completing the quest the given number of times
+    Repeat(Box<QuestDef>, u32),
+    /// A [`QuestMajority`] completing at least 2 of the 3 quests
+    Majority(Box<QuestDef>, Box<QuestDef>, Box<QuestDef>),
+    /// A [`QuestSequence`] completing each of the quests in order
+    Sequence(Vec<QuestDef>),
+    /// A [`QuestAtLeast`] completing at least the given number of the quests
+    AtLeast(usize, Vec<QuestDef>),
+    /// A [`QuestAll`] completing all of the quests
+    All(Vec<QuestDef>),
+    /// A [`QuestAny`] completing any one of the quests
+    Any(Vec<QuestDef>),
+    /// A [`QuestWithin`] completing the quest within the given amount of time,
+    /// or else failing or starting again
+    Within(Box<QuestDef>, u32, TimeUnit, OnTimeout),
+}
+
+impl QuestDef {
+    /// Build a new [`Quest`] described by this definition
+    ///
+    /// The built quest starts from scratch, as if it had just been reset
+    pub fn build(&self) -> Box<dyn Quest<Event>> {
+        self.build_with(None)
+    }
+
+    /// Build a new [`Quest`] described by this definition, whose collect
+    /// quests count the objects in the given holdings
+    pub fn build_holding(&self, holdings: &Holdings) -> Box<dyn Quest<Event>> {
+        self.build_with(Some(holdings))
+    }
+
+    /// Build a new [`Quest`], sharing the holdings with its collect quests
+    fn build_with(&self, holdings: Option<&Holdings>) -> Box<dyn Quest<Event>> {
+        match self {
+            QuestDef::Walk(block) => Box::new(WalkQuest::new(block.clone())),
+            QuestDef::WalkRepeat(block, count) => {
+                Box::new(WalkRepeatQuest::new(block.clone(), *count))
+            }
+            QuestDef::Avoid(block, count) => Box::new(AvoidBlockQuest::new(block.clone(), *count)),
+            QuestDef::Collect(item, count) => Box::new(CollectQuest::with_holdings(
+                *item,
+                *count,
+                holdings.cloned(),
+            )),
+            QuestDef::Reach(x, y) => Box::new(ReachQuest::new((*x, *y))),
+            QuestDef::Region(region) => Box::new(RegionQuest::new(region.clone())),
+            QuestDef::Then(q1, q2) => Box::new(QuestThen::new(
+                q1.build_with(holdings),
+                q2.build_with(holdings),
+            )),
+            QuestDef::Repeat(q, count) => {
+                Box::new(QuestRepeat::new(q.build_with(holdings), *count))
+            }
+            QuestDef::Majority(q1, q2, q3) => Box::new(QuestMajority::new(
+                q1.build_with(holdings),
+                q2.build_with(holdings),
+                q3.build_with(holdings),
+            )),
+            QuestDef::Sequence(qs) => Box::new(QuestSequence::new(build_all(qs, holdings))),
+            QuestDef::AtLeast(count, qs) => {
+                Box::new(QuestAtLeast::new(build_all(qs, holdings), *count))
+            }
+            QuestDef::All(qs) => Box::new(QuestAll::new(build_all(qs, holdings))),
+            QuestDef::Any(qs) => Box::new(QuestAny::new(build_all(qs, holdings))),
+            QuestDef::Within(q, limit, unit, on_timeout) => Box::new(QuestWithin::new(
+                q.build_with(holdings),
+                *limit,
+                *unit,
+                *on_timeout,
+            )),
+        }
+    }
+
+    /// Find the blocks this quest needs, but which cannot be found
+    ///
+    /// `available` tells whether a block can be found, e.g. on a map. Blocks
+    /// that are only needed by optional sub-quests are not reported, as long as
+    /// enough of the other sub-quests could still be completed
+    ///
+    /// # Returns
+    ///
+    /// The missing blocks, or an empty [`Vec`] if nothing is missing
+    pub fn missing_blocks(&self, available: &dyn Fn(&Block) -> bool) -> Vec<Block> {
+        match self {
+            QuestDef::Walk(block) | QuestDef::WalkRepeat(block, _) => {
+                if available(block) {
+                    vec![]
+                } else {
+                    vec![block.clone()]
+                }
+            }
+            QuestDef::Collect(item, _) => {
+                QuestDef::Walk(Block::Object(*item)).missing_blocks(available)
+            }
+            QuestDef::Avoid(_, _) | QuestDef::Reach(_, _) | QuestDef::Region(_) => vec![],
+            QuestDef::Then(q1, q2) => missing_blocks_at_least([&**q1, &**q2], 2, available),
+            QuestDef::Repeat(q, _) | QuestDef::Within(q, _, _, _) => q.missing_blocks(available),
+            QuestDef::Majority(q1, q2, q3) => {
+                missing_blocks_at_least([&**q1, &**q2, &**q3], 2, available)
+            }
+            QuestDef::Sequence(qs) | QuestDef::All(qs) => {
+                missing_blocks_at_least(qs, qs.len(), available)
+            }
+            QuestDef::AtLeast(count, qs) => missing_blocks_at_least(qs, *count, available),
+            QuestDef::Any(qs) => missing_blocks_at_least(qs, 1, available),
+        }
+    }
+
+    /// Find the parts of this quest that can never be completed, however the
+    /// game is played, e.g. `Any([])` or an `AtLeast` needing more quests than
+    /// it has
+    ///
+    /// # Returns
+    ///
+    /// A description of each problem, or an empty [`Vec`] if there are none
+    pub fn impossible(&self) -> Vec<String> {
+        let (problem, children): (Option<String>, Vec<&QuestDef>) = match self {
+            QuestDef::Walk(_)
+            | QuestDef::WalkRepeat(_, _)
+            | QuestDef::Avoid(_, _)
+            | QuestDef::Collect(_, _)
+            | QuestDef::Reach(_, _)
+            | QuestDef::Region(_) => (None, vec![]),
+            QuestDef::Then(q1, q2) => (None, vec![q1, q2]),
+            QuestDef::Repeat(q, _) | QuestDef::Within(q, _, _, _) => (None, vec![q]),
+            QuestDef::Majority(q1, q2, q3) => (None, vec![q1, q2, q3]),
+            QuestDef::Sequence(qs) | QuestDef::All(qs) => (None, qs.iter().collect()),
+            QuestDef::Any(qs) => (
+                qs.is_empty()
+                    .then(|| String::from("Any([]) has no quests, so one can never be completed")),
+                qs.iter().collect(),
+            ),
+            QuestDef::AtLeast(count, qs) => (
+                (*count > qs.len()).then(|| {
+                    format!(
+                        "AtLeast({count}, ..) has only {} quests, so {count} can never be completed",
+                        qs.len()
+                    )
+                }),
+                qs.iter().collect(),
+            ),
+        };
+
+        problem
+            .into_iter()
+            .chain(children.into_iter().flat_map(QuestDef::impossible))
+            .collect()
+    }
+
+    /// Find the names of the regions of the map this quest refers to, in the
+    /// order they first appear
+    pub fn region_names(&self) -> Vec<&str> {
+        let children: Vec<&QuestDef> = match self {
+            QuestDef::Region(Region::Named(name)) => return vec![name],
+            QuestDef::Walk(_)
+            | QuestDef::WalkRepeat(_, _)
+            | QuestDef::Avoid(_, _)
+            | QuestDef::Collect(_, _)
+            | QuestDef::Reach(_, _)
+            | QuestDef::Region(Region::Rect(_)) => vec![],
+            QuestDef::Then(q1, q2) => vec![q1, q2],
+            QuestDef::Repeat(q, _) | QuestDef::Within(q, _, _, _) => vec![q],
+            QuestDef::Majority(q1, q2, q3) => vec![q1, q2, q3],
+            QuestDef::Sequence(qs)
+            | QuestDef::AtLeast(_, qs)
+            | QuestDef::All(qs)
+            | QuestDef::Any(qs) => qs.iter().collect(),
+        };
+
+        let mut names = Vec::new();
+        for name in children.into_iter().flat_map(QuestDef::region_names) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+/// Find the blocks missing from the given quests, when at least `count` of them
+/// must be completed
+fn missing_blocks_at_least<'a>(
+    defs: impl IntoIterator<Item = &'a QuestDef>,
+    count: usize,
+    available: &dyn Fn(&Block) -> bool,
+) -> Vec<Block> {
+    let missing: Vec<Vec<Block>> = defs
+        .into_iter()
+        .map(|def| def.missing_blocks(available))
+        .collect();
+    if missing.iter().filter(|blocks| blocks.is_empty()).count() >= count {
+        return vec![];
+    }
+
+    let mut blocks: Vec<Block> = Vec::new();
+    for block in missing.into_iter().flatten() {
+        if !blocks.contains(&block) {
+            blocks.push(block);
+        }
+    }
+    blocks
+}
+
+/// Build each of the given quest definitions
+fn build_all(defs: &[QuestDef], holdings: Option<&Holdings>) -> Vec<Box<dyn Quest<Event>>> {
+    defs.iter().map(|def| def.build_with(holdings)).collect()
+}
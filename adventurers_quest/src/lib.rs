@@ -6,6 +6,9 @@
 //! # Quickstart
 //!
 //! ```
+//! # use adventurers_quest::quests::combinators::{QuestMajority, QuestRepeat, QuestThen};
+//! # use adventurers_quest::quests::{WalkQuest, WalkRepeatQuest};
+//! # use blocks::Block;
 //! let quest = Box::new(QuestMajority::new(
 //!     Box::new(QuestThen::new(
 //!         Box::new(QuestRepeat::new(Box::new(WalkQuest::new(Block::Sand)), 5)),
@@ -21,24 +24,62 @@
 //!     )),
 //! ));
 //! ```
+//!
+//! Quests can also be described as data with a [`QuestDef`](definition::QuestDef),
+//! which is useful for loading quests from a file:
+//!
+//! ```
+//! # use adventurers_quest::definition::QuestDef;
+//! # use blocks::Block;
+//! let def = QuestDef::Then(
+//!     Box::new(QuestDef::Repeat(Box::new(QuestDef::Walk(Block::Sand)), 5)),
+//!     Box::new(QuestDef::Walk(Block::Object('x'))),
+//! );
+//! let quest = def.build();
+//! ```
 
 #![warn(missing_docs)]
 
 use blocks::Block;
+use progress::QuestProgress;
+use serde::{Deserialize, Serialize};
+use snapshot::{QuestSnapshot, SnapshotError};
 
+pub mod definition;
+pub mod progress;
 pub mod quests;
+pub mod snapshot;
+pub mod solver;
 
 /// The status of a quest
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum QuestStatus {
     /// A complete quest
     Complete,
 
     /// An incomplete quest
     Ongoing,
+
+    /// A quest that can no longer be completed
+    Failed,
+}
+
+impl std::fmt::Display for QuestStatus {
+    /// Show the status as a checkbox
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuestStatus::Complete => write!(f, "[✅]"),
+            QuestStatus::Ongoing => write!(f, "[ ]"),
+            QuestStatus::Failed => write!(f, "[❌]"),
+        }
+    }
 }
 
 /// This is what a "quest" should do.
+///
+/// The combinators in [`quests::combinators`] work with any type of event, while
+/// the primitive quests in [`quests`] work with this crate's [`Event`].
+///
 /// Note that all `Quests` implement Debug and Display.
 /// Traits' Debug implementation does not matter, but
 /// they should implement [`std::fmt::Display`] to show
@@ -49,30 +90,132 @@ pub trait Quest<Event>: std::fmt::Display + std::fmt::Debug {
     /// Returns the updated status of the quest after the event has been processed
     fn register_event(&mut self, event: &Event) -> QuestStatus;
 
+    /// The current status of the quest, which is the status last returned by
+    /// [`Quest::register_event`].
+    fn status(&self) -> QuestStatus;
+
     /// Reset the quest, so that players can restart.
     fn reset(&mut self);
+
+    /// The events that would make progress on the quest if they happened next,
+    /// e.g. walking on a block, to give the player a hint.
+    ///
+    /// Returns no events if the quest is not ongoing, or if progress is not made by
+    /// particular events. This is the default. The same event may be returned more
+    /// than once by quests with several sub-quests.
+    fn targets(&self) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// The progress of the quest and its sub-quests, as a tree.
+    ///
+    /// The [`Display`](std::fmt::Display) of the quest shows this tree.
+    fn progress(&self) -> QuestProgress;
+
+    /// Take a snapshot of the current state of the quest, so that it can be saved.
+    fn snapshot(&self) -> QuestSnapshot;
+
+    /// Restore the state of the quest from a snapshot.
+    ///
+    /// Returns an error if the snapshot was not taken from a quest with the same structure,
+    /// in which case the quest may be left partially restored.
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError>;
 }
 
-/// An event that contains various information that may affect the progress
-/// of a quest
-#[derive(Debug)]
-pub struct Event {
-    /// If some, the event indicates the movement to some block.
-    block: Option<Block>,
+/// An event that happens in the game and may affect the progress of a quest
+///
+/// # JSON
+///
+/// Events are serialized as JSON with serde's default enum representation, and
+/// blocks as their variant names:
+///
+/// | Event                            | JSON                                      |
+/// |----------------------------------|-------------------------------------------|
+/// | `Move(Some(Block::Sand))`        | `{"Move":"Sand"}`                         |
+/// | `Move(Some(Block::Object('x')))` | `{"Move":{"Object":"x"}}`                 |
+/// | `Move(None)`                     | `{"Move":null}`                           |
+/// | `Reach(3, 4)`                    | `{"Reach":[3,4]}`                         |
+/// | `InRegion(..)`                   | `{"InRegion":"North cave"}`               |
+/// | `Blocked(Block::Barrier)`        | `{"Blocked":"Barrier"}`                   |
+/// | `Pickup { item: 'x', held: 2 }`  | `{"Pickup":{"item":"x","held":2}}`        |
+/// | `ReadSign(..)`                   | `{"ReadSign":"Hello"}`                    |
+/// | `Damage(1)`                      | `{"Damage":1}`                            |
+/// | `Drown`, `Tick`                  | `"Drown"`, `"Tick"`                       |
+///
+/// # Event logs
+///
+/// The `adventurers` game's `--jsonl <file>` option writes a JSON Lines file with
+/// one record per event registered with the quest, in order:
+///
+/// ```json
+/// {"type":"event","event":{"Move":"Sand"},"position":{"x":3,"y":2},"health":10,"status":"Ongoing"}
+/// ```
+///
+/// - `event`: the event, as above
+/// - `position`, `health`: the state of the player after the event happened
+/// - `status`: the [`QuestStatus`] returned by [`Quest::register_event`]
+///
+/// Game ticks are only logged when they complete or fail the quest, e.g. when a
+/// time limit runs out, since they happen many times a second. Events are no
+/// longer registered once the quest is complete or failed. The last line is a
+/// summary of the session:
+///
+/// ```json
+/// {"type":"summary","events":42,"position":{"x":5,"y":9},"health":10,"status":"Complete",
+///  "won":true,"failed":false,"dead":false,"inventory":{"x":1},"quest":{...}}
+/// ```
+///
+/// - `events`: the number of event records
+/// - `won`, `failed`, `dead`: how the session ended, all `false` if it was quit
+/// - `inventory`: the number of each object held, keyed by the object's character
+/// - `quest`: the [`QuestProgress`] tree of the quest (shown on one line in the file)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event {
+    /// The player moved onto a tile. If some, the tile contains the given block
+    Move(Option<Block>),
+    /// The player moved onto the given coordinate
+    Reach(i32, i32),
+    /// The player moved onto a coordinate within the named region of the map
+    InRegion(String),
+    /// The player was stopped from moving by the given block
+    Blocked(Block),
+    /// The player picked up an object
+    Pickup {
+        /// The character of the object picked up
+        item: char,
+        /// The number of these objects the player now holds
+        held: u32,
+    },
+    /// The player read a sign with the given message
+    ReadSign(String),
+    /// The player took the given amount of damage
+    Damage(i32),
+    /// The player drowned
+    Drown,
+    /// A game tick passed
+    Tick,
 }
 
 impl Event {
     /// Create a new [`Event`] to indicate movement to a Block
     pub fn on_block(block: Block) -> Self {
-        Self { block: Some(block) }
+        Self::Move(Some(block))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::quests::combinators::{QuestMajority, QuestRepeat, QuestThen};
-    use crate::quests::{WalkQuest, WalkRepeatQuest};
+    use crate::definition::QuestDef;
+    use crate::quests::combinators::{
+        OnTimeout, QuestAll, QuestAny, QuestAtLeast, QuestMajority, QuestRepeat, QuestSequence,
+        QuestThen, QuestWithin, TimeUnit,
+    };
+    use crate::quests::{
+        AvoidBlockQuest, CollectQuest, ReachQuest, Rect, Region, RegionQuest, WalkQuest,
+        WalkRepeatQuest,
+    };
+    use crate::snapshot::QuestSnapshot;
 
     /// The player wins the game if they walk over 5 sand blocks
     #[test]
@@ -403,4 +546,938 @@ mod tests {
             QuestStatus::Complete
         );
     }
+
+    /// Quest definitions build the same quest trees as the hand-written constructors
+    #[test]
+    fn quest_def_test() {
+        let def: QuestDef = ron::from_str(
+            "Majority(
+                Then(Repeat(Walk(Sand), 5), Walk(Object('x'))),
+                Then(Walk(Object('x')), Walk(Grass)),
+                Repeat(WalkRepeat(Water, 9), 2),
+            )",
+        )
+        .unwrap();
+        let q3 = Box::new(QuestMajority::new(
+            Box::new(QuestThen::new(
+                Box::new(QuestRepeat::new(Box::new(WalkQuest::new(Block::Sand)), 5)),
+                Box::new(WalkQuest::new(Block::Object('x'))),
+            )),
+            Box::new(QuestThen::new(
+                Box::new(WalkQuest::new(Block::Object('x'))),
+                Box::new(WalkQuest::new(Block::Grass)),
+            )),
+            Box::new(QuestRepeat::new(
+                Box::new(WalkRepeatQuest::new(Block::Water, 9)),
+                2,
+            )),
+        ));
+        assert_eq!(def.build().to_string(), q3.to_string());
+
+        let mut q1 = ron::from_str::<QuestDef>("Repeat(Walk(Sand), 2)")
+            .unwrap()
+            .build();
+        assert_eq!(
+            q1.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q1.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+
+        // repeating a quest no times is complete from the start, even after a reset
+        let mut q0 = ron::from_str::<QuestDef>("Repeat(Walk(Sand), 0)")
+            .unwrap()
+            .build();
+        assert_eq!(q0.status(), QuestStatus::Complete);
+        q0.reset();
+        assert_eq!(q0.status(), QuestStatus::Complete);
+    }
+
+    #[test]
+    fn quest_def_malformed_test() {
+        assert!(ron::from_str::<QuestDef>("Repeat(Walk(Sand))").is_err());
+        assert!(ron::from_str::<QuestDef>("Walk(Lava)").is_err());
+    }
+
+    /// "Walk on sand, grass and water, in any order"
+    #[test]
+    fn quest_all_test() {
+        let mut q = QuestAll::new(vec![
+            Box::new(WalkQuest::new(Block::Sand)),
+            Box::new(WalkQuest::new(Block::Grass)),
+            Box::new(WalkQuest::new(Block::Water)),
+        ]);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Complete
+        );
+    }
+
+    /// "Walk on either sand or grass"
+    #[test]
+    fn quest_any_test() {
+        let mut q = QuestAny::new(vec![
+            Box::new(WalkQuest::new(Block::Sand)),
+            Box::new(WalkQuest::new(Block::Grass)),
+        ]);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Complete
+        );
+        assert_eq!(
+            q.to_string(),
+            "[✅] You must complete any one of these quests:\n\t[ ] Walk on a Sand block\n\t[✅] Walk on a Grass block"
+        );
+    }
+
+    /// "Complete at least 2 of 4 quests"
+    #[test]
+    fn quest_at_least_test() {
+        let mut q = QuestAtLeast::new(
+            vec![
+                Box::new(WalkQuest::new(Block::Sand)),
+                Box::new(WalkQuest::new(Block::Grass)),
+                Box::new(WalkQuest::new(Block::Rock)),
+                Box::new(QuestRepeat::new(Box::new(WalkQuest::new(Block::Water)), 2)),
+            ],
+            2,
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Rock)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Complete
+        );
+
+        // the sub-quests still make progress once the quest is complete
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+        assert!(q.to_string().starts_with(
+            "[✅] You must complete at least 2 of these quests:\n\t[✅] Walk on a Sand block"
+        ));
+
+        q.reset();
+        assert_eq!(
+            q.to_string(),
+            "[ ] You must complete at least 2 of these quests:\n\t[ ] Walk on a Sand block\n\t[ ] Walk on a Grass block\n\t[ ] Walk on a Rock block\n\tRepeat 2 times:\n\t\t[ ] Walk on a Water block"
+        );
+    }
+
+    /// "Walk on sand, then grass, then an 'x' object"
+    #[test]
+    fn quest_sequence_test() {
+        let mut q = QuestSequence::new(vec![
+            Box::new(WalkQuest::new(Block::Sand)),
+            Box::new(WalkQuest::new(Block::Grass)),
+            Box::new(WalkQuest::new(Block::Object('x'))),
+        ]);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Object('x'))),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Object('x'))),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Object('x'))),
+            QuestStatus::Complete
+        );
+    }
+
+    #[test]
+    fn quest_def_n_ary_test() {
+        let mut q =
+            ron::from_str::<QuestDef>("AtLeast(1, [All([Walk(Sand), Walk(Grass)]), Any([])])")
+                .unwrap()
+                .build();
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+    }
+
+    #[test]
+    fn impossible_test() {
+        let impossible = |def: &str| ron::from_str::<QuestDef>(def).unwrap().impossible();
+
+        assert!(impossible("AtLeast(2, [Walk(Sand), Walk(Grass)])").is_empty());
+        assert!(impossible("Sequence([])").is_empty());
+        assert_eq!(
+            impossible("Then(Walk(Sand), Any([]))"),
+            vec![String::from(
+                "Any([]) has no quests, so one can never be completed"
+            )]
+        );
+        assert_eq!(
+            impossible("Repeat(AtLeast(3, [Walk(Sand), Any([])]), 2)"),
+            vec![
+                String::from("AtLeast(3, ..) has only 2 quests, so 3 can never be completed"),
+                String::from("Any([]) has no quests, so one can never be completed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_blocks_test() {
+        let available = |block: &Block| matches!(block, Block::Sand | Block::Object('x'));
+        let missing = |def: &str| {
+            ron::from_str::<QuestDef>(def)
+                .unwrap()
+                .missing_blocks(&available)
+        };
+
+        assert_eq!(missing("Then(Walk(Sand), Collect('x', 3))"), vec![]);
+        assert_eq!(
+            missing("Then(Walk(Water), Collect('y', 3))"),
+            vec![Block::Water, Block::Object('y')]
+        );
+        assert_eq!(missing("Any([Walk(Water), Walk(Sand)])"), vec![]);
+        assert_eq!(
+            missing("Majority(Walk(Water), Walk(Sand), Avoid(Grass, 2))"),
+            vec![]
+        );
+        assert_eq!(
+            missing("AtLeast(2, [Walk(Water), Walk(Sand), Walk(Grass)])"),
+            vec![Block::Water, Block::Grass]
+        );
+    }
+
+    #[test]
+    fn targets_test() {
+        let mut q = ron::from_str::<QuestDef>(
+            "Majority(Then(Repeat(Walk(Sand), 2), Collect('x', 1)), \
+             Sequence([Walk(Object('x')), Walk(Grass)]), Avoid(Water, 5))",
+        )
+        .unwrap()
+        .build();
+        let targets = |blocks: &[Block]| -> Vec<Event> {
+            blocks.iter().cloned().map(Event::on_block).collect()
+        };
+        assert_eq!(q.targets(), targets(&[Block::Sand, Block::Object('x')]));
+
+        q.register_event(&Event::on_block(Block::Object('x')));
+        assert_eq!(q.targets(), targets(&[Block::Sand, Block::Grass]));
+
+        q.register_event(&Event::on_block(Block::Sand));
+        q.register_event(&Event::on_block(Block::Sand));
+        assert_eq!(q.targets(), targets(&[Block::Object('x'), Block::Grass]));
+
+        q.register_event(&Event::on_block(Block::Grass));
+        assert_eq!(q.status(), QuestStatus::Ongoing);
+        assert_eq!(q.targets(), targets(&[Block::Object('x')]));
+
+        q.register_event(&Event::Pickup { item: 'x', held: 1 });
+        assert_eq!(q.targets(), vec![]);
+    }
+
+    /// Coordinates and regions to go to are targets too
+    #[test]
+    fn position_targets_test() {
+        let mut q = ron::from_str::<QuestDef>(
+            "Any([Reach(3, 4), Region(Named(\"cave\")), \
+             Region(Rect((min: (0, 0), max: (1, 1))))])",
+        )
+        .unwrap()
+        .build();
+        assert_eq!(
+            q.targets(),
+            vec![
+                Event::Reach(3, 4),
+                Event::InRegion(String::from("cave")),
+                Event::Reach(0, 0),
+                Event::Reach(1, 0),
+                Event::Reach(0, 1),
+                Event::Reach(1, 1),
+            ]
+        );
+
+        q.register_event(&Event::Reach(3, 4));
+        assert_eq!(q.targets(), vec![]);
+    }
+
+    #[test]
+    fn progress_test() {
+        use crate::progress::{ProgressCount, QuestKind};
+
+        let mut q = ron::from_str::<QuestDef>("Then(Repeat(WalkRepeat(Sand, 2), 3), Walk(Grass))")
+            .unwrap()
+            .build();
+        for _ in 0..3 {
+            q.register_event(&Event::on_block(Block::Sand));
+        }
+
+        let progress = q.progress();
+        assert_eq!(progress.kind, QuestKind::Then);
+        assert_eq!(progress.status, QuestStatus::Ongoing);
+        assert_eq!(progress.count, None);
+
+        let repeat = &progress.children[0];
+        assert_eq!(repeat.kind, QuestKind::Repeat);
+        assert_eq!(repeat.description, "Repeat 2 times:");
+        assert_eq!(
+            repeat.count,
+            Some(ProgressCount {
+                current: 1,
+                target: 3
+            })
+        );
+        assert_eq!(
+            repeat.children[0].count,
+            Some(ProgressCount {
+                current: 1,
+                target: 2
+            })
+        );
+        assert_eq!(progress.children[1].kind, QuestKind::Walk);
+        assert_eq!(progress.children[1].children, vec![]);
+
+        assert_eq!(q.to_string(), progress.to_string());
+    }
+
+    /// A toy event type, for another game that can reuse the combinators
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Toy {
+        Ping,
+        Pong,
+        Fail,
+    }
+
+    /// A toy quest, completed after hearing its event a number of times, and
+    /// failed by [`Toy::Fail`]
+    #[derive(Debug)]
+    struct ToyQuest {
+        event: Toy,
+        target_count: u32,
+        count: u32,
+        status: QuestStatus,
+    }
+
+    impl ToyQuest {
+        fn new(event: Toy, target_count: u32) -> Box<Self> {
+            Box::new(Self {
+                event,
+                target_count,
+                count: 0,
+                status: QuestStatus::Ongoing,
+            })
+        }
+    }
+
+    impl std::fmt::Display for ToyQuest {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.progress())
+        }
+    }
+
+    impl Quest<Toy> for ToyQuest {
+        fn register_event(&mut self, event: &Toy) -> QuestStatus {
+            if self.status == QuestStatus::Ongoing {
+                if *event == Toy::Fail {
+                    self.status = QuestStatus::Failed;
+                } else if *event == self.event {
+                    self.count += 1;
+                    if self.count == self.target_count {
+                        self.status = QuestStatus::Complete;
+                    }
+                }
+            }
+            self.status
+        }
+
+        fn status(&self) -> QuestStatus {
+            self.status
+        }
+
+        fn reset(&mut self) {
+            self.count = 0;
+            self.status = QuestStatus::Ongoing;
+        }
+
+        fn progress(&self) -> progress::QuestProgress {
+            progress::QuestProgress::new(
+                progress::QuestKind::Custom,
+                format!("Hear {:?} {} times", self.event, self.target_count),
+                self.status,
+            )
+            .count(self.count, self.target_count)
+        }
+
+        fn snapshot(&self) -> QuestSnapshot {
+            QuestSnapshot::new(self.status).counters(vec![self.count])
+        }
+
+        fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+            snapshot.expect_shape(1, 0)?;
+            self.count = snapshot.counters[0];
+            self.status = snapshot.status;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn toy_then_repeat_test() {
+        let mut q: QuestThen<Toy> = QuestThen::new(
+            Box::new(QuestRepeat::new(ToyQuest::new(Toy::Ping, 2), 2)),
+            ToyQuest::new(Toy::Pong, 1),
+        );
+        for _ in 0..3 {
+            assert_eq!(q.register_event(&Toy::Ping), QuestStatus::Ongoing);
+        }
+        assert_eq!(q.register_event(&Toy::Pong), QuestStatus::Ongoing);
+        assert_eq!(q.register_event(&Toy::Ping), QuestStatus::Ongoing);
+        assert_eq!(q.register_event(&Toy::Pong), QuestStatus::Complete);
+        assert_eq!(q.targets(), vec![]);
+        assert_eq!(
+            q.to_string(),
+            "[✅] You must, in order, complete each of these quests:\n\tRepeat 0 times:\n\t\t[✅] Hear Ping 2 times\n\t[✅] Hear Pong 1 times"
+        );
+    }
+
+    #[test]
+    fn toy_majority_test() {
+        let mut q = QuestMajority::new(
+            ToyQuest::new(Toy::Ping, 1),
+            ToyQuest::new(Toy::Pong, 1),
+            ToyQuest::new(Toy::Ping, 2),
+        );
+        assert_eq!(q.register_event(&Toy::Ping), QuestStatus::Ongoing);
+
+        let snapshot = q.snapshot();
+        assert_eq!(q.register_event(&Toy::Fail), QuestStatus::Failed);
+
+        q.restore(&snapshot).unwrap();
+        assert_eq!(q.register_event(&Toy::Pong), QuestStatus::Complete);
+    }
+
+    #[test]
+    fn toy_n_ary_test() {
+        let mut q: QuestSequence<Toy> = QuestSequence::new(vec![
+            Box::new(QuestAll::new(vec![
+                ToyQuest::new(Toy::Ping, 1),
+                ToyQuest::new(Toy::Pong, 1),
+            ])),
+            Box::new(QuestAny::new(vec![
+                ToyQuest::new(Toy::Ping, 2),
+                ToyQuest::new(Toy::Pong, 2),
+            ])),
+            Box::new(QuestAtLeast::new(vec![ToyQuest::new(Toy::Ping, 1)], 1)),
+        ]);
+        assert_eq!(q.register_event(&Toy::Ping), QuestStatus::Ongoing);
+        assert_eq!(q.register_event(&Toy::Pong), QuestStatus::Ongoing);
+        assert_eq!(q.register_event(&Toy::Pong), QuestStatus::Ongoing);
+        assert_eq!(q.progress().count.unwrap().current, 2);
+        assert_eq!(q.register_event(&Toy::Ping), QuestStatus::Complete);
+    }
+
+    /// "Walk on 3 blocks without touching water", failing through a then quest
+    #[test]
+    fn avoid_block_test() {
+        let mut q = QuestThen::new(
+            Box::new(AvoidBlockQuest::new(Block::Water, 3)),
+            Box::new(WalkQuest::new(Block::Sand)),
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Failed
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Failed
+        );
+        assert_eq!(
+            q.to_string(),
+            "[❌] You must, in order, complete each of these quests:\n\t[❌] Walk on 3 blocks without walking on a Water block\n\t[ ] Walk on a Sand block"
+        );
+
+        q.reset();
+        for _ in 0..3 {
+            assert_eq!(
+                q.register_event(&Event::on_block(Block::Grass)),
+                QuestStatus::Ongoing
+            );
+        }
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+    }
+
+    /// A majority fails once a majority of its sub-quests can no longer be completed
+    #[test]
+    fn failure_propagation_test() {
+        let mut q = QuestMajority::new(
+            Box::new(QuestRepeat::new(
+                Box::new(AvoidBlockQuest::new(Block::Water, 5)),
+                2,
+            )),
+            Box::new(AvoidBlockQuest::new(Block::Sand, 5)),
+            Box::new(WalkQuest::new(Block::Rock)),
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Failed
+        );
+
+        let mut q = QuestAtLeast::new(
+            vec![
+                Box::new(AvoidBlockQuest::new(Block::Water, 5)),
+                Box::new(AvoidBlockQuest::new(Block::Sand, 5)),
+                Box::new(WalkQuest::new(Block::Rock)),
+            ],
+            1,
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Rock)),
+            QuestStatus::Complete
+        );
+    }
+
+    /// Only movement events affect the walk primitives
+    #[test]
+    fn non_movement_events_test() {
+        let mut q = QuestThen::new(
+            Box::new(WalkRepeatQuest::new(Block::Water, 2)),
+            Box::new(WalkQuest::new(Block::Sand)),
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(q.register_event(&Event::Damage(1)), QuestStatus::Ongoing);
+        assert_eq!(q.register_event(&Event::Tick), QuestStatus::Ongoing);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::Blocked(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+
+        let mut q = WalkRepeatQuest::new(Block::Water, 2);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(q.register_event(&Event::Move(None)), QuestStatus::Ongoing);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+    }
+
+    /// "Hold 3 'x' objects", which is about the inventory rather than walking
+    #[test]
+    fn collect_test() {
+        let mut q = CollectQuest::new('x', 3);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Object('x'))),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::Pickup { item: 'y', held: 3 }),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::Pickup { item: 'x', held: 2 }),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::Pickup { item: 'x', held: 3 }),
+            QuestStatus::Complete
+        );
+        assert_eq!(q.to_string(), "[✅] Hold 3 'x' objects in your inventory");
+    }
+
+    /// "Walk on sand, then hold 2 'x' objects", where the objects are collected
+    /// before walking on sand
+    #[test]
+    fn collect_after_then_test() {
+        use std::collections::BTreeMap;
+
+        use crate::progress::ProgressCount;
+        use crate::quests::Holdings;
+
+        let holdings = Holdings::default();
+        let mut q = ron::from_str::<QuestDef>("Then(Walk(Sand), Collect('x', 2))")
+            .unwrap()
+            .build_holding(&holdings);
+        for held in 1..=2 {
+            holdings.set(&BTreeMap::from([('x', held)]));
+            q.register_event(&Event::Pickup { item: 'x', held });
+        }
+        assert_eq!(
+            q.progress().children[1].count,
+            Some(ProgressCount {
+                current: 0,
+                target: 2
+            })
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+
+        // after a reset, the objects held are counted again
+        holdings.set(&BTreeMap::from([('x', 3)]));
+        q.reset();
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+        assert_eq!(
+            q.progress().children[1].count,
+            Some(ProgressCount {
+                current: 2,
+                target: 2
+            })
+        );
+    }
+
+    /// Restoring a snapshot onto a freshly built quest gives back the same quest
+    #[test]
+    fn snapshot_test() {
+        let def = ron::from_str::<QuestDef>(include_str!("../../quests/q3.ron")).unwrap();
+        let mut q3 = def.build();
+        for block in [
+            Block::Sand,
+            Block::Sand,
+            Block::Object('x'),
+            Block::Water,
+            Block::Water,
+        ] {
+            q3.register_event(&Event::on_block(block));
+        }
+
+        let saved = ron::to_string(&q3.snapshot()).unwrap();
+        let mut restored = def.build();
+        restored
+            .restore(&ron::from_str::<QuestSnapshot>(&saved).unwrap())
+            .unwrap();
+        assert_eq!(restored.to_string(), q3.to_string());
+        assert_eq!(restored.snapshot(), q3.snapshot());
+
+        for _ in 0..7 {
+            assert_eq!(
+                restored.register_event(&Event::on_block(Block::Water)),
+                q3.register_event(&Event::on_block(Block::Water))
+            );
+        }
+        assert_eq!(restored.to_string(), q3.to_string());
+
+        let mut q1 = ron::from_str::<QuestDef>(include_str!("../../quests/q1.ron"))
+            .unwrap()
+            .build();
+        assert!(q1.restore(&q3.snapshot()).is_err());
+    }
+
+    /// "Walk on 2 sand blocks within 3 moves"
+    #[test]
+    fn within_moves_test() {
+        let mut q = QuestWithin::new(
+            Box::new(QuestRepeat::new(Box::new(WalkQuest::new(Block::Sand)), 2)),
+            3,
+            TimeUnit::Moves,
+            OnTimeout::Fail,
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        // ticks don't count towards a limit in moves
+        assert_eq!(q.register_event(&Event::Tick), QuestStatus::Ongoing);
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.to_string(),
+            "[ ] Within 1 move:\n\tRepeat 1 times:\n\t\t[ ] Walk on a Sand block"
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Failed
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Failed
+        );
+
+        // the last move still counts if it completes the sub-quest
+        q.reset();
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Grass)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Complete
+        );
+    }
+
+    /// A within quest is over from the start if its sub-quest is, or it has no
+    /// time and can't start again
+    #[test]
+    fn within_over_test() {
+        let build = |def: &str| ron::from_str::<QuestDef>(def).unwrap().build();
+
+        let mut q = build("Within(Repeat(Walk(Sand), 0), 5, Moves, Fail)");
+        assert_eq!(q.status(), QuestStatus::Complete);
+        q.reset();
+        assert_eq!(q.status(), QuestStatus::Complete);
+
+        let mut q = build("Within(Walk(Sand), 0, Ticks, Fail)");
+        assert_eq!(q.status(), QuestStatus::Failed);
+        q.reset();
+        assert_eq!(q.status(), QuestStatus::Failed);
+        assert_eq!(
+            q.to_string(),
+            "[❌] Within 0 ticks:\n\t[ ] Walk on a Sand block"
+        );
+
+        let q = build("Within(Walk(Sand), 0, Ticks, Reset)");
+        assert_eq!(q.status(), QuestStatus::Ongoing);
+    }
+
+    /// When the time runs out, a resetting within quest starts its sub-quest again
+    #[test]
+    fn within_reset_test() {
+        use crate::progress::{ProgressCount, QuestKind};
+
+        let def =
+            ron::from_str::<QuestDef>("Within(WalkRepeat(Water, 2), 2, Ticks, Reset)").unwrap();
+        let mut q = def.build();
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(q.register_event(&Event::Tick), QuestStatus::Ongoing);
+        assert_eq!(q.progress().kind, QuestKind::Within(TimeUnit::Ticks));
+        assert_eq!(
+            q.progress().count,
+            Some(ProgressCount {
+                current: 1,
+                target: 2
+            })
+        );
+        assert_eq!(q.register_event(&Event::Tick), QuestStatus::Ongoing);
+        assert_eq!(
+            q.progress().count,
+            Some(ProgressCount {
+                current: 0,
+                target: 2
+            })
+        );
+
+        // the water walked on before the reset no longer counts
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Ongoing
+        );
+        let saved = q.snapshot();
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Complete
+        );
+
+        let mut restored = def.build();
+        restored.restore(&saved).unwrap();
+        assert_eq!(
+            restored.register_event(&Event::on_block(Block::Water)),
+            QuestStatus::Complete
+        );
+    }
+
+    /// "Get to the lighthouse at (120, 4)", whatever block is there
+    #[test]
+    fn reach_test() {
+        let mut q = ReachQuest::new((120, 4));
+        assert_eq!(
+            q.register_event(&Event::on_block(Block::Sand)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::Reach(4, 120)),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(q.to_string(), "[ ] Reach (120, 4)");
+        assert_eq!(
+            q.register_event(&Event::Reach(120, 4)),
+            QuestStatus::Complete
+        );
+    }
+
+    /// Regions are entered by moving onto any coordinate within them
+    #[test]
+    fn region_test() {
+        let rect = Rect {
+            min: (0, -5),
+            max: (3, 0),
+        };
+        let mut q = RegionQuest::new(Region::Rect(rect));
+        assert_eq!(q.register_event(&Event::Reach(4, -1)), QuestStatus::Ongoing);
+        assert_eq!(q.register_event(&Event::Reach(3, 1)), QuestStatus::Ongoing);
+        assert_eq!(q.to_string(), "[ ] Go to the area from (0, -5) to (3, 0)");
+        assert_eq!(
+            q.register_event(&Event::Reach(3, -5)),
+            QuestStatus::Complete
+        );
+
+        let def = ron::from_str::<QuestDef>(
+            r#"Then(Region(Named("northern cave")), Any([Reach(1, 1), Region(Named("exit"))]))"#,
+        )
+        .unwrap();
+        assert_eq!(def.region_names(), vec!["northern cave", "exit"]);
+
+        let mut q = def.build();
+        assert_eq!(
+            q.register_event(&Event::InRegion(String::from("exit"))),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::InRegion(String::from("northern cave"))),
+            QuestStatus::Ongoing
+        );
+        assert_eq!(
+            q.register_event(&Event::InRegion(String::from("exit"))),
+            QuestStatus::Complete
+        );
+    }
+
+    /// A row of blocks walked from the left end, where each block can be walked
+    /// on once
+    struct Row {
+        blocks: Vec<Block>,
+        position: usize,
+        quest: Box<dyn Quest<Event>>,
+    }
+
+    impl solver::Playable for Row {
+        type State = (usize, QuestSnapshot);
+        type Move = bool;
+
+        fn state(&self) -> Self::State {
+            (self.position, self.quest.snapshot())
+        }
+
+        fn restore(&mut self, state: &Self::State) {
+            self.position = state.0;
+            self.quest.restore(&state.1).unwrap();
+        }
+
+        fn moves(&self, state: &Self::State) -> Vec<bool> {
+            let mut moves = vec![];
+            if state.0 > 0 {
+                moves.push(false);
+            }
+            if state.0 + 1 < self.blocks.len() {
+                moves.push(true);
+            }
+            moves
+        }
+
+        fn play(&mut self, right: bool) -> QuestStatus {
+            self.position = if right {
+                self.position + 1
+            } else {
+                self.position - 1
+            };
+            self.quest
+                .register_event(&Event::on_block(self.blocks[self.position].clone()))
+        }
+    }
+
+    #[test]
+    fn solver_test() {
+        use solver::{solve, Solution};
+        let row = |quest: &str| Row {
+            blocks: vec![Block::Grass, Block::Sand, Block::Grass, Block::Water],
+            position: 0,
+            quest: ron::from_str::<QuestDef>(quest).unwrap().build(),
+        };
+
+        assert_eq!(
+            solve(&mut row("Then(Walk(Water), Walk(Sand))"), 100),
+            Solution::Solved(vec![true, true, true, false, false])
+        );
+        assert_eq!(
+            solve(&mut row("Avoid(Grass, 1)"), 100),
+            Solution::Solved(vec![true])
+        );
+        assert!(matches!(
+            solve(&mut row("Walk(Rock)"), 100),
+            Solution::Impossible { .. }
+        ));
+    }
 }
@@ -0,0 +1,137 @@
+//! # Quest progress
+//!
+//! A progress tree is a structured, read-only view of how far along a quest tree
+//! is. Unlike the [`Display`] string of a quest, it can be inspected directly,
+//! e.g. by user interfaces, exports and tests.
+//!
+//! The [`Display`] of every quest is the [`Display`] of its progress tree.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::quests::combinators::TimeUnit;
+use crate::QuestStatus;
+
+/// The kind of quest a progress node describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuestKind {
+    /// A [`WalkQuest`](crate::quests::WalkQuest)
+    Walk,
+    /// A [`WalkRepeatQuest`](crate::quests::WalkRepeatQuest)
+    WalkRepeat,
+    /// An [`AvoidBlockQuest`](crate::quests::AvoidBlockQuest)
+    Avoid,
+    /// A [`CollectQuest`](crate::quests::CollectQuest)
+    Collect,
+    /// A [`ReachQuest`](crate::quests::ReachQuest)
+    Reach,
+    /// A [`RegionQuest`](crate::quests::RegionQuest)
+    Region,
+    /// A [`QuestThen`](crate::quests::combinators::QuestThen)
+    Then,
+    /// A [`QuestRepeat`](crate::quests::combinators::QuestRepeat)
+    Repeat,
+    /// A [`QuestMajority`](crate::quests::combinators::QuestMajority)
+    Majority,
+    /// A [`QuestSequence`](crate::quests::combinators::QuestSequence)
+    Sequence,
+    /// A [`QuestAtLeast`](crate::quests::combinators::QuestAtLeast)
+    AtLeast,
+    /// A [`QuestAll`](crate::quests::combinators::QuestAll)
+    All,
+    /// A [`QuestAny`](crate::quests::combinators::QuestAny)
+    Any,
+    /// A [`QuestWithin`](crate::quests::combinators::QuestWithin) with a time
+    /// limit counted in the given unit
+    Within(TimeUnit),
+    /// A quest defined outside of this crate, e.g. for another game's events
+    Custom,
+}
+
+/// A count of how many things have been done towards a quest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressCount {
+    /// How many have been done so far, e.g. blocks walked or sub-quests completed
+    pub current: u32,
+    /// How many must be done
+    pub target: u32,
+}
+
+/// The progress of a quest and its sub-quests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestProgress {
+    /// The kind of quest
+    pub kind: QuestKind,
+    /// A description of what must be done to complete the quest
+    pub description: String,
+    /// The status of the quest
+    pub status: QuestStatus,
+    /// How much of the quest has been done, if the quest counts anything
+    pub count: Option<ProgressCount>,
+    /// The progress of the sub-quests, in order
+    pub children: Vec<QuestProgress>,
+}
+
+impl QuestProgress {
+    /// Create a new progress node with no count or sub-quests
+    pub fn new(kind: QuestKind, description: String, status: QuestStatus) -> Self {
+        Self {
+            kind,
+            description,
+            status,
+            count: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Set how much of the quest has been done
+    pub fn count(mut self, current: u32, target: u32) -> Self {
+        self.count = Some(ProgressCount { current, target });
+        self
+    }
+
+    /// Set the progress of the sub-quests
+    pub fn children(mut self, children: Vec<QuestProgress>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Find the first ongoing quest in the tree that passes the given test,
+    /// searching depth first with each quest before its sub-quests
+    pub fn find_ongoing(
+        &self,
+        predicate: &dyn Fn(&QuestProgress) -> bool,
+    ) -> Option<&QuestProgress> {
+        if self.status != QuestStatus::Ongoing {
+            return None;
+        }
+        if predicate(self) {
+            return Some(self);
+        }
+        self.children
+            .iter()
+            .find_map(|child| child.find_ongoing(predicate))
+    }
+}
+
+impl Display for QuestProgress {
+    /// Show the status and description of the quest, followed by each of its
+    /// sub-quests indented on their own lines
+    ///
+    /// Repeat quests show how many times are left instead of their status
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.kind == QuestKind::Repeat {
+            write!(f, "{}", self.description)?;
+        } else {
+            write!(f, "{} {}", self.status, self.description)?;
+        }
+
+        for child in &self.children {
+            let child = child.to_string().replace('\n', "\n\t");
+            write!(f, "\n\t{child}")?;
+        }
+
+        Ok(())
+    }
+}
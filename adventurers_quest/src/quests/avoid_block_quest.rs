@@ -0,0 +1,112 @@
+//! # The avoid block quest module
+//!
+//! The avoid block quest is a primitive quest that requires a certain number of
+//! blocks to be walked on without ever walking on a target block
+//!
+//! The quest fails as soon as an event is fired that indicates movement onto the
+//! target block
+
+use std::fmt::Display;
+
+use blocks::Block;
+
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// The state of the avoid block primitive quest
+#[derive(Debug)]
+pub struct AvoidBlockQuest {
+    /// The target block that must not be walked on
+    target_block: Block,
+    /// The number of blocks that must be walked on to complete the quest
+    target_count: u32,
+    /// The number of blocks that have been walked on so far
+    blocks_walked: u32,
+    /// The status of the avoid block quest
+    status: QuestStatus,
+}
+
+impl AvoidBlockQuest {
+    /// Create a new avoid block quest given the block to avoid and the number of
+    /// blocks that must be walked on while avoiding it
+    pub fn new(target_block: Block, target_count: u32) -> Self {
+        Self {
+            target_block,
+            target_count,
+            blocks_walked: 0,
+            status: if target_count == 0 {
+                QuestStatus::Complete
+            } else {
+                QuestStatus::Ongoing
+            },
+        }
+    }
+}
+
+impl Display for AvoidBlockQuest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl Quest<Event> for AvoidBlockQuest {
+    fn register_event(&mut self, event: &Event) -> QuestStatus {
+        if self.status == QuestStatus::Ongoing {
+            if let Event::Move(block) = event {
+                if block.as_ref() == Some(&self.target_block) {
+                    self.status = QuestStatus::Failed;
+                    return self.status;
+                }
+
+                self.blocks_walked += 1;
+                if self.blocks_walked == self.target_count {
+                    self.status = QuestStatus::Complete;
+                }
+            }
+        }
+
+        self.status
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    fn reset(&mut self) {
+        self.blocks_walked = 0;
+        self.status = if self.target_count == 0 {
+            QuestStatus::Complete
+        } else {
+            QuestStatus::Ongoing
+        };
+    }
+
+    fn targets(&self) -> Vec<Event> {
+        // every block but the target block makes progress
+        vec![]
+    }
+
+    fn progress(&self) -> QuestProgress {
+        QuestProgress::new(
+            QuestKind::Avoid,
+            format!(
+                "Walk on {} blocks without walking on a {} block",
+                self.target_count, self.target_block
+            ),
+            self.status,
+        )
+        .count(self.blocks_walked, self.target_count)
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status).counters(vec![self.blocks_walked])
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(1, 0)?;
+        self.blocks_walked = snapshot.counters[0];
+        self.status = snapshot.status;
+        Ok(())
+    }
+}
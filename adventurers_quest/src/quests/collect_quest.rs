@@ -0,0 +1,149 @@
+//! # The collect quest module
+//!
+//! The collect quest is a primitive quest that requires the player to hold a
+//! certain number of a target object in their inventory
+//!
+//! Whenever an object is picked up, the number of those objects now held is
+//! checked. A game can also share the player's inventory with its collect
+//! quests through [`Holdings`], which they read when they start, are reset, and
+//! on every event, so objects picked up before the quest started still count
+//! towards it
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use blocks::Block;
+
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// The number of each object the player holds, shared by a game with its
+/// collect quests
+///
+/// Clones share the same holdings
+#[derive(Debug, Clone, Default)]
+pub struct Holdings(Rc<RefCell<BTreeMap<char, u32>>>);
+
+impl Holdings {
+    /// The number of the given object held
+    pub fn get(&self, item: char) -> u32 {
+        self.0.borrow().get(&item).copied().unwrap_or_default()
+    }
+
+    /// Replace the holdings with the number of each object now held
+    pub fn set(&self, items: &BTreeMap<char, u32>) {
+        self.0.borrow_mut().clone_from(items);
+    }
+}
+
+/// The state of the collect primitive quest
+#[derive(Debug)]
+pub struct CollectQuest {
+    /// The character of the object to collect
+    target_item: char,
+    /// The number of objects that must be held to complete the quest
+    target_count: u32,
+    /// The number of objects last known to be held
+    held: u32,
+    /// The player's holdings, if shared by the game
+    holdings: Option<Holdings>,
+    /// The status of the collect quest
+    status: QuestStatus,
+}
+
+impl CollectQuest {
+    /// Create a new collect quest given the object to collect and the number of
+    /// them that must be held
+    pub fn new(target_item: char, target_count: u32) -> Self {
+        Self::with_holdings(target_item, target_count, None)
+    }
+
+    /// Create a new collect quest that counts the objects in the given
+    /// holdings, if any, including those held before it started
+    pub fn with_holdings(target_item: char, target_count: u32, holdings: Option<Holdings>) -> Self {
+        let mut quest = Self {
+            target_item,
+            target_count,
+            held: 0,
+            holdings,
+            status: QuestStatus::Ongoing,
+        };
+        quest.reset();
+        quest
+    }
+}
+
+impl Display for CollectQuest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl Quest<Event> for CollectQuest {
+    fn register_event(&mut self, event: &Event) -> QuestStatus {
+        match event {
+            Event::Pickup { item, held } if *item == self.target_item => self.held = *held,
+            _ => {}
+        }
+        if let Some(holdings) = &self.holdings {
+            self.held = holdings.get(self.target_item);
+        }
+
+        if self.status == QuestStatus::Ongoing && self.held >= self.target_count {
+            self.status = QuestStatus::Complete;
+        }
+        self.status
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    fn reset(&mut self) {
+        self.held = self
+            .holdings
+            .as_ref()
+            .map_or(0, |holdings| holdings.get(self.target_item));
+        self.status = if self.held >= self.target_count {
+            QuestStatus::Complete
+        } else {
+            QuestStatus::Ongoing
+        };
+    }
+
+    fn targets(&self) -> Vec<Event> {
+        // objects are picked up after walking on them
+        if self.status == QuestStatus::Ongoing {
+            vec![Event::on_block(Block::Object(self.target_item))]
+        } else {
+            vec![]
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        QuestProgress::new(
+            QuestKind::Collect,
+            format!(
+                "Hold {} {} objects in your inventory",
+                self.target_count,
+                Block::Object(self.target_item)
+            ),
+            self.status,
+        )
+        .count(self.held.min(self.target_count), self.target_count)
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status).counters(vec![self.held])
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(1, 0)?;
+        self.held = snapshot.counters[0];
+        self.status = snapshot.status;
+        Ok(())
+    }
+}
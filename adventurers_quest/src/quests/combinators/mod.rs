@@ -2,11 +2,24 @@
 //!
 //! Combinator quests are quests themselves that can be completed.
 //! Their completion status depends on the completion status of their comprised quests.
+//!
+//! Combinators are generic over the type of event their sub-quests take, which is
+//! this crate's [`Event`](crate::Event) unless given otherwise.
 
+pub mod quest_all;
+pub mod quest_any;
+pub mod quest_at_least;
 pub mod quest_majority;
 pub mod quest_repeat;
+pub mod quest_sequence;
 pub mod quest_then;
+pub mod quest_within;
 
+pub use quest_all::*;
+pub use quest_any::*;
+pub use quest_at_least::*;
 pub use quest_majority::*;
 pub use quest_repeat::*;
+pub use quest_sequence::*;
 pub use quest_then::*;
+pub use quest_within::*;
@@ -0,0 +1,62 @@
+//! # Quest all module
+//!
+//! The 'all' quest is a quest that is completed when every one of its sub-quests
+//! is completed, in any order
+
+use std::fmt::{Debug, Display};
+
+use super::QuestAtLeast;
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// The state of the all combinator quest
+#[derive(Debug)]
+pub struct QuestAll<E = Event>(QuestAtLeast<E>);
+
+impl<E> QuestAll<E> {
+    /// Create a new all quest given the sub-quests that must all be completed
+    pub fn new(quests: Vec<Box<dyn Quest<E>>>) -> Self {
+        let target_count = quests.len();
+        Self(QuestAtLeast::new(quests, target_count))
+    }
+}
+
+impl<E: Debug> Display for QuestAll<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl<E: Debug> Quest<E> for QuestAll<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        self.0.register_event(event)
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.0.status()
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn targets(&self) -> Vec<E> {
+        self.0.targets()
+    }
+
+    fn progress(&self) -> QuestProgress {
+        self.0.progress_with(
+            QuestKind::All,
+            String::from("You must complete all of these quests:"),
+        )
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        self.0.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        self.0.restore(snapshot)
+    }
+}
@@ -0,0 +1,61 @@
+//! # Quest any module
+//!
+//! The 'any' quest is a quest that is completed as soon as one of its sub-quests
+//! is completed
+
+use std::fmt::{Debug, Display};
+
+use super::QuestAtLeast;
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// The state of the any combinator quest
+#[derive(Debug)]
+pub struct QuestAny<E = Event>(QuestAtLeast<E>);
+
+impl<E> QuestAny<E> {
+    /// Create a new any quest given the sub-quests, one of which must be completed
+    pub fn new(quests: Vec<Box<dyn Quest<E>>>) -> Self {
+        Self(QuestAtLeast::new(quests, 1))
+    }
+}
+
+impl<E: Debug> Display for QuestAny<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl<E: Debug> Quest<E> for QuestAny<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        self.0.register_event(event)
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.0.status()
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn targets(&self) -> Vec<E> {
+        self.0.targets()
+    }
+
+    fn progress(&self) -> QuestProgress {
+        self.0.progress_with(
+            QuestKind::Any,
+            String::from("You must complete any one of these quests:"),
+        )
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        self.0.snapshot()
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        self.0.restore(snapshot)
+    }
+}
@@ -0,0 +1,158 @@
+//! # Quest at least module
+//!
+//! The 'at least' quest is a quest that is completed when at least a certain
+//! number of its sub-quests are completed
+//!
+//! Sub-quests can be completed in any order, and progress is made on all of them
+//! at the same time
+//!
+//! The at least quest fails once too many sub-quests have failed for the
+//! required number of them to still be completed
+
+use std::fmt::{Debug, Display};
+
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// The state of the at least combinator quest
+#[derive(Debug)]
+pub struct QuestAtLeast<E = Event> {
+    /// The sub-quests
+    quests: Vec<Box<dyn Quest<E>>>,
+    /// The status of each sub-quest
+    statuses: Vec<QuestStatus>,
+    /// The number of sub-quests that must be completed for the combinator quest
+    /// to be considered completed
+    target_count: usize,
+    /// The status of the at least quest
+    status: QuestStatus,
+}
+
+impl<E> QuestAtLeast<E> {
+    /// Create a new at least quest given the sub-quests and the number of them
+    /// that must be completed
+    ///
+    /// All sub-quests start off as incomplete, and the at least quest itself
+    /// starts off as ongoing (unless no sub-quests need to be completed)
+    pub fn new(quests: Vec<Box<dyn Quest<E>>>, target_count: usize) -> Self {
+        Self {
+            statuses: vec![QuestStatus::Ongoing; quests.len()],
+            quests,
+            target_count,
+            status: if target_count == 0 {
+                QuestStatus::Complete
+            } else {
+                QuestStatus::Ongoing
+            },
+        }
+    }
+
+    /// Count the sub-quests with the given status
+    fn count(&self, status: QuestStatus) -> usize {
+        self.statuses.iter().filter(|s| **s == status).count()
+    }
+
+    /// The progress of the quest, described as the given kind of quest
+    pub(crate) fn progress_with(&self, kind: QuestKind, description: String) -> QuestProgress {
+        QuestProgress::new(kind, description, self.status)
+            .count(
+                self.count(QuestStatus::Complete) as u32,
+                self.target_count as u32,
+            )
+            .children(self.quests.iter().map(|q| q.progress()).collect())
+    }
+}
+
+impl<E: Debug> Display for QuestAtLeast<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl<E: Debug> Quest<E> for QuestAtLeast<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        // check which sub-quests are complete or failed, which carries on once
+        // the at least quest is over so that their progress is still shown
+        for (q, status) in self.quests.iter_mut().zip(self.statuses.iter_mut()) {
+            if *status == QuestStatus::Ongoing {
+                *status = q.register_event(event);
+            }
+        }
+        if self.status != QuestStatus::Ongoing {
+            return self.status;
+        }
+
+        // check if enough sub-quests are complete, or if too many have failed
+        let completed_count = self.count(QuestStatus::Complete);
+        let ongoing_count = self.count(QuestStatus::Ongoing);
+
+        if completed_count >= self.target_count {
+            self.status = QuestStatus::Complete;
+        } else if completed_count + ongoing_count < self.target_count {
+            self.status = QuestStatus::Failed;
+        }
+
+        self.status
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    fn reset(&mut self) {
+        self.quests.iter_mut().for_each(|q| q.reset());
+        self.statuses
+            .iter_mut()
+            .for_each(|s| *s = QuestStatus::Ongoing);
+        self.status = if self.target_count == 0 {
+            QuestStatus::Complete
+        } else {
+            QuestStatus::Ongoing
+        };
+    }
+
+    fn targets(&self) -> Vec<E> {
+        if self.status != QuestStatus::Ongoing {
+            return vec![];
+        }
+
+        let mut targets = Vec::new();
+        for (q, status) in self.quests.iter().zip(&self.statuses) {
+            if *status == QuestStatus::Ongoing {
+                targets.extend(q.targets());
+            }
+        }
+        targets
+    }
+
+    fn progress(&self) -> QuestProgress {
+        self.progress_with(
+            QuestKind::AtLeast,
+            format!(
+                "You must complete at least {} of these quests:",
+                self.target_count
+            ),
+        )
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status).children(self.quests.iter().map(|q| q.snapshot()).collect())
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(0, self.quests.len())?;
+        for ((q, status), child) in self
+            .quests
+            .iter_mut()
+            .zip(self.statuses.iter_mut())
+            .zip(&snapshot.children)
+        {
+            q.restore(child)?;
+            // the status of each sub-quest is kept in its own snapshot
+            *status = child.status;
+        }
+        self.status = snapshot.status;
+        Ok(())
+    }
+}
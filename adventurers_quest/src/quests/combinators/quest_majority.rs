@@ -2,111 +2,66 @@
 //!
 //! The majority quest is a quest that is completed when 2 out of its 3
 //! sub-quests (i.e. the majority) are completed
+//!
+//! The majority quest fails once 2 of its sub-quests have failed, since a
+//! majority can then no longer be completed
 
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 
+use super::QuestAtLeast;
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
 use crate::{Event, Quest, QuestStatus};
 
 /// The state of the majority combinator quest
 #[derive(Debug)]
-pub struct QuestMajority {
-    /// The first sub-quest
-    q1: Box<dyn Quest<Event>>,
-    /// The second sub-quest
-    q2: Box<dyn Quest<Event>>,
-    /// The third sub-quest
-    q3: Box<dyn Quest<Event>>,
-    /// Whether the first sub-quest is completed
-    q1_complete: bool,
-    /// Whether the second sub-quest is completed
-    q2_complete: bool,
-    /// Whether the third sub-quest is completed
-    q3_complete: bool,
-    /// The status of the majority quest
-    status: QuestStatus,
-}
+pub struct QuestMajority<E = Event>(QuestAtLeast<E>);
 
-impl QuestMajority {
+impl<E> QuestMajority<E> {
     /// Create a new majority quest given three sub-quests
     ///
     /// All sub-quests start off as incomplete, and the majority quest itself
     /// starts off as ongoing
-    pub fn new(
-        q1: Box<dyn Quest<Event>>,
-        q2: Box<dyn Quest<Event>>,
-        q3: Box<dyn Quest<Event>>,
-    ) -> Self {
-        Self {
-            q1,
-            q2,
-            q3,
-            q1_complete: false,
-            q2_complete: false,
-            q3_complete: false,
-            status: QuestStatus::Ongoing,
-        }
+    pub fn new(q1: Box<dyn Quest<E>>, q2: Box<dyn Quest<E>>, q3: Box<dyn Quest<E>>) -> Self {
+        Self(QuestAtLeast::new(vec![q1, q2, q3], 2))
     }
 }
 
-impl Display for QuestMajority {
+impl<E: Debug> Display for QuestMajority<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let q1 = format!("{}", self.q1);
-        let q1 = q1.replace('\n', "\n\t");
-        let q2 = format!("{}", self.q2);
-        let q2 = q2.replace('\n', "\n\t");
-        let q3 = format!("{}", self.q3);
-        let q3 = q3.replace('\n', "\n\t");
-        match self.status {
-            QuestStatus::Complete => write!(
-                f,
-                "[✅] You must complete at least 2 of these quests:\n\t{}\n\t{}\n\t{}",
-                q1, q2, q3
-            ),
-            QuestStatus::Ongoing => write!(
-                f,
-                "[ ] You must complete at least 2 of these quests:\n\t{}\n\t{}\n\t{}",
-                q1, q2, q3
-            ),
-        }
+        write!(f, "{}", self.progress())
     }
 }
 
-impl Quest<Event> for QuestMajority {
-    fn register_event(&mut self, event: &Event) -> QuestStatus {
-        // check if sub-quest 1 is complete
-        if !self.q1_complete && self.q1.register_event(event) == QuestStatus::Complete {
-            self.q1_complete = true;
-        }
+impl<E: Debug> Quest<E> for QuestMajority<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        self.0.register_event(event)
+    }
 
-        // check if sub-quest 2 is complete
-        if !self.q2_complete && self.q2.register_event(event) == QuestStatus::Complete {
-            self.q2_complete = true;
-        }
+    fn status(&self) -> QuestStatus {
+        self.0.status()
+    }
 
-        // check if sub-quest 3 is complete
-        if !self.q3_complete && self.q3.register_event(event) == QuestStatus::Complete {
-            self.q3_complete = true;
-        }
+    fn reset(&mut self) {
+        self.0.reset();
+    }
 
-        // check if the majority of sub-quests are complete
-        let completed_count = if self.q1_complete { 1 } else { 0 }
-            + if self.q2_complete { 1 } else { 0 }
-            + if self.q3_complete { 1 } else { 0 };
+    fn targets(&self) -> Vec<E> {
+        self.0.targets()
+    }
 
-        if completed_count >= 2 {
-            self.status = QuestStatus::Complete;
-        }
+    fn progress(&self) -> QuestProgress {
+        self.0.progress_with(
+            QuestKind::Majority,
+            String::from("You must complete at least 2 of these quests:"),
+        )
+    }
 
-        self.status
+    fn snapshot(&self) -> QuestSnapshot {
+        self.0.snapshot()
     }
 
-    fn reset(&mut self) {
-        self.q1.reset();
-        self.q2.reset();
-        self.q3.reset();
-        self.q1_complete = false;
-        self.q2_complete = false;
-        self.q3_complete = false;
-        self.status = QuestStatus::Ongoing;
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        self.0.restore(snapshot)
     }
 }
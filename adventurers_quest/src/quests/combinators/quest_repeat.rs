@@ -2,16 +2,20 @@
 //!
 //! The repeat quest is a quest that is completed when its sub-quest is completed
 //! a certain amount of times
+//!
+//! If the sub-quest fails, the repeat quest fails
 
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
 use crate::{Event, Quest, QuestStatus};
 
 /// The state of the repeat combinator quest
 #[derive(Debug)]
-pub struct QuestRepeat {
+pub struct QuestRepeat<E = Event> {
     /// The quest that must be repeated
-    q: Box<dyn Quest<Event>>,
+    q: Box<dyn Quest<E>>,
     /// The number of times the sub-quest must be completed
     /// for the combinator quest to be considered completed
     target_count: u32,
@@ -21,10 +25,10 @@ pub struct QuestRepeat {
     status: QuestStatus,
 }
 
-impl QuestRepeat {
+impl<E> QuestRepeat<E> {
     /// Create a new repeat quest given the quest to be repeated and the number of
     /// times to complete it in order for the combinator quest to be considered completed
-    pub fn new(q: Box<dyn Quest<Event>>, target_count: u32) -> Self {
+    pub fn new(q: Box<dyn Quest<E>>, target_count: u32) -> Self {
         Self {
             q,
             target_count,
@@ -38,38 +42,75 @@ impl QuestRepeat {
     }
 }
 
-impl Display for QuestRepeat {
+impl<E: Debug> Display for QuestRepeat<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let q = format!("{}", self.q);
-        let q = q.replace('\n', "\n\t");
-        write!(
-            f,
-            "Repeat {} times:\n\t{}",
-            self.target_count - self.completed_count,
-            q
-        )
+        write!(f, "{}", self.progress())
     }
 }
 
-impl Quest<Event> for QuestRepeat {
-    fn register_event(&mut self, event: &Event) -> QuestStatus {
-        if self.status != QuestStatus::Complete
-            && self.q.register_event(event) == QuestStatus::Complete
-        {
-            self.completed_count += 1;
-            if self.completed_count == self.target_count {
-                self.status = QuestStatus::Complete;
-            } else {
-                self.q.reset();
+impl<E: Debug> Quest<E> for QuestRepeat<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        if self.status == QuestStatus::Ongoing {
+            match self.q.register_event(event) {
+                QuestStatus::Complete => {
+                    self.completed_count += 1;
+                    if self.completed_count == self.target_count {
+                        self.status = QuestStatus::Complete;
+                    } else {
+                        self.q.reset();
+                    }
+                }
+                QuestStatus::Failed => self.status = QuestStatus::Failed,
+                QuestStatus::Ongoing => {}
             }
         }
 
         self.status
     }
 
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
     fn reset(&mut self) {
         self.q.reset();
         self.completed_count = 0;
-        self.status = QuestStatus::Ongoing;
+        self.status = if self.target_count == 0 {
+            QuestStatus::Complete
+        } else {
+            QuestStatus::Ongoing
+        };
+    }
+
+    fn targets(&self) -> Vec<E> {
+        if self.status == QuestStatus::Ongoing {
+            self.q.targets()
+        } else {
+            vec![]
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        QuestProgress::new(
+            QuestKind::Repeat,
+            format!("Repeat {} times:", self.target_count - self.completed_count),
+            self.status,
+        )
+        .count(self.completed_count, self.target_count)
+        .children(vec![self.q.progress()])
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status)
+            .counters(vec![self.completed_count])
+            .children(vec![self.q.snapshot()])
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(1, 1)?;
+        self.q.restore(&snapshot.children[0])?;
+        self.completed_count = snapshot.counters[0];
+        self.status = snapshot.status;
+        Ok(())
     }
 }
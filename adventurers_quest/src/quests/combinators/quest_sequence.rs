@@ -0,0 +1,117 @@
+//! # Quest sequence module
+//!
+//! The sequence quest is a quest that contains any number of sub-quests that
+//! must be completed in order for the sequence quest to be considered completed
+//!
+//! Progress on a sub-quest is not made until every sub-quest before it is completed
+//!
+//! If any sub-quest fails, the sequence quest fails
+
+use std::fmt::{Debug, Display};
+
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// The state of the sequence quest combinator
+#[derive(Debug)]
+pub struct QuestSequence<E = Event> {
+    /// The quests to be completed, in order
+    quests: Vec<Box<dyn Quest<E>>>,
+    /// The status of the sequence quest
+    status: QuestStatus,
+}
+
+impl<E> QuestSequence<E> {
+    /// Create a new sequence quest given the sub-quests to be completed in order
+    pub fn new(quests: Vec<Box<dyn Quest<E>>>) -> Self {
+        Self {
+            status: if quests.is_empty() {
+                QuestStatus::Complete
+            } else {
+                QuestStatus::Ongoing
+            },
+            quests,
+        }
+    }
+}
+
+impl<E: Debug> Display for QuestSequence<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl<E: Debug> Quest<E> for QuestSequence<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        if self.status == QuestStatus::Ongoing {
+            // the event is passed along until a sub-quest is still incomplete
+            self.status = QuestStatus::Complete;
+            for q in &mut self.quests {
+                match q.register_event(event) {
+                    QuestStatus::Complete => {}
+                    status => {
+                        self.status = status;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.status
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    fn reset(&mut self) {
+        self.quests.iter_mut().for_each(|q| q.reset());
+        self.status = if self.quests.is_empty() {
+            QuestStatus::Complete
+        } else {
+            QuestStatus::Ongoing
+        };
+    }
+
+    fn targets(&self) -> Vec<E> {
+        if self.status != QuestStatus::Ongoing {
+            return vec![];
+        }
+
+        // only the first incomplete sub-quest can make progress
+        self.quests
+            .iter()
+            .find(|q| q.status() != QuestStatus::Complete)
+            .map_or(vec![], |q| q.targets())
+    }
+
+    fn progress(&self) -> QuestProgress {
+        let children: Vec<QuestProgress> = self.quests.iter().map(|q| q.progress()).collect();
+        let completed_count = children
+            .iter()
+            .filter(|child| child.status == QuestStatus::Complete)
+            .count();
+
+        QuestProgress::new(
+            QuestKind::Sequence,
+            String::from("You must, in order, complete each of these quests:"),
+            self.status,
+        )
+        .count(completed_count as u32, children.len() as u32)
+        .children(children)
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status).children(self.quests.iter().map(|q| q.snapshot()).collect())
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(0, self.quests.len())?;
+        for (q, child) in self.quests.iter_mut().zip(&snapshot.children) {
+            q.restore(child)?;
+        }
+        self.status = snapshot.status;
+        Ok(())
+    }
+}
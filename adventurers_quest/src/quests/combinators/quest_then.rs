@@ -4,25 +4,29 @@
 //! must be completed in order for the 'then' quest to be considered completed
 //!
 //! Progress on the second quest is not made until the first quest is completed
+//!
+//! If either quest fails, the 'then' quest fails
 
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
 use crate::{Event, Quest, QuestStatus};
 
 /// The state of the then quest combinator
 #[derive(Debug)]
-pub struct QuestThen {
+pub struct QuestThen<E = Event> {
     /// The first quest to be completed
-    q1: Box<dyn Quest<Event>>,
+    q1: Box<dyn Quest<E>>,
     /// The second quest to be completed
-    q2: Box<dyn Quest<Event>>,
+    q2: Box<dyn Quest<E>>,
     /// The status of the then quest
     status: QuestStatus,
 }
 
-impl QuestThen {
+impl<E> QuestThen<E> {
     /// Create a new then quest given the two sub-quests to be completed in order
-    pub fn new(q1: Box<dyn Quest<Event>>, q2: Box<dyn Quest<Event>>) -> Self {
+    pub fn new(q1: Box<dyn Quest<E>>, q2: Box<dyn Quest<E>>) -> Self {
         Self {
             q1,
             q2,
@@ -31,41 +35,62 @@ impl QuestThen {
     }
 }
 
-impl Display for QuestThen {
+impl<E: Debug> Display for QuestThen<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let q1 = format!("{}", self.q1);
-        let q1 = q1.replace('\n', "\n\t");
-        let q2 = format!("{}", self.q2);
-        let q2 = q2.replace('\n', "\n\t");
-        match self.status {
-            QuestStatus::Complete => write!(
-                f,
-                "[✅] You must, in order, complete each of these quests:\n\t{}\n\t{}",
-                q1, q2
-            ),
-            QuestStatus::Ongoing => write!(
-                f,
-                "[ ] You must, in order, complete each of these quests:\n\t{}\n\t{}",
-                q1, q2
-            ),
-        }
+        write!(f, "{}", self.progress())
     }
 }
 
-impl Quest<Event> for QuestThen {
-    fn register_event(&mut self, event: &Event) -> QuestStatus {
-        if self.q1.register_event(event) == QuestStatus::Complete
-            && self.q2.register_event(event) == QuestStatus::Complete
-        {
-            self.status = QuestStatus::Complete;
+impl<E: Debug> Quest<E> for QuestThen<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        if self.status == QuestStatus::Ongoing {
+            self.status = match self.q1.register_event(event) {
+                QuestStatus::Complete => self.q2.register_event(event),
+                status => status,
+            };
         }
 
         self.status
     }
 
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
     fn reset(&mut self) {
         self.q1.reset();
         self.q2.reset();
         self.status = QuestStatus::Ongoing;
     }
+
+    fn targets(&self) -> Vec<E> {
+        if self.status != QuestStatus::Ongoing {
+            vec![]
+        } else if self.q1.status() == QuestStatus::Complete {
+            self.q2.targets()
+        } else {
+            self.q1.targets()
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        QuestProgress::new(
+            QuestKind::Then,
+            String::from("You must, in order, complete each of these quests:"),
+            self.status,
+        )
+        .children(vec![self.q1.progress(), self.q2.progress()])
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status).children(vec![self.q1.snapshot(), self.q2.snapshot()])
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(0, 2)?;
+        self.q1.restore(&snapshot.children[0])?;
+        self.q2.restore(&snapshot.children[1])?;
+        self.status = snapshot.status;
+        Ok(())
+    }
 }
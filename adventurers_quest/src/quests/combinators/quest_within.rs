@@ -0,0 +1,190 @@
+//! # Quest within module
+//!
+//! The 'within' quest is a quest that gives its sub-quest a time limit, counted
+//! in either game ticks or player moves
+//!
+//! If the sub-quest is not completed in time, the within quest either fails, or
+//! resets the sub-quest and starts the time limit again
+
+use std::fmt::{Debug, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// What a time limit is counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeUnit {
+    /// Game ticks
+    Ticks,
+    /// Player moves
+    Moves,
+}
+
+impl TimeUnit {
+    /// The given amount of time, e.g. `1 move` or `3 moves`
+    pub fn amount(self, count: u32) -> String {
+        match (self, count) {
+            (TimeUnit::Ticks, 1) => String::from("1 tick"),
+            (TimeUnit::Moves, 1) => String::from("1 move"),
+            _ => format!("{count} {self}"),
+        }
+    }
+}
+
+impl Display for TimeUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeUnit::Ticks => write!(f, "ticks"),
+            TimeUnit::Moves => write!(f, "moves"),
+        }
+    }
+}
+
+/// What happens when a time limit runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OnTimeout {
+    /// The within quest fails
+    Fail,
+    /// The sub-quest is reset, and the time limit starts again
+    Reset,
+}
+
+/// An event that time can be counted in
+pub trait TimedEvent {
+    /// Whether the event is one unit of the given time passing
+    fn counts_as(&self, unit: TimeUnit) -> bool;
+}
+
+impl TimedEvent for Event {
+    fn counts_as(&self, unit: TimeUnit) -> bool {
+        match unit {
+            TimeUnit::Ticks => matches!(self, Event::Tick),
+            TimeUnit::Moves => matches!(self, Event::Move(_)),
+        }
+    }
+}
+
+/// The state of the within quest combinator
+#[derive(Debug)]
+pub struct QuestWithin<E = Event> {
+    /// The quest that must be completed in time
+    q: Box<dyn Quest<E>>,
+    /// The amount of time the sub-quest has to be completed in
+    limit: u32,
+    /// What the time limit is counted in
+    unit: TimeUnit,
+    /// What happens when the time limit runs out
+    on_timeout: OnTimeout,
+    /// The amount of time that has passed since the time limit started
+    elapsed: u32,
+    /// The status of the within quest
+    status: QuestStatus,
+}
+
+impl<E> QuestWithin<E> {
+    /// Create a new within quest given the sub-quest, its time limit, and what
+    /// happens when the time limit runs out
+    pub fn new(q: Box<dyn Quest<E>>, limit: u32, unit: TimeUnit, on_timeout: OnTimeout) -> Self {
+        let mut quest = Self {
+            q,
+            limit,
+            unit,
+            on_timeout,
+            elapsed: 0,
+            status: QuestStatus::Ongoing,
+        };
+        quest.status = quest.initial_status();
+        quest
+    }
+
+    /// The status of the within quest before any time has passed
+    ///
+    /// It is over if the sub-quest already is, and fails straight away if there
+    /// is no time to complete the sub-quest in and no starting again
+    fn initial_status(&self) -> QuestStatus {
+        match self.q.status() {
+            QuestStatus::Ongoing if self.limit == 0 && self.on_timeout == OnTimeout::Fail => {
+                QuestStatus::Failed
+            }
+            status => status,
+        }
+    }
+}
+
+impl<E: TimedEvent + Debug> Display for QuestWithin<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl<E: TimedEvent + Debug> Quest<E> for QuestWithin<E> {
+    fn register_event(&mut self, event: &E) -> QuestStatus {
+        if self.status != QuestStatus::Ongoing {
+            return self.status;
+        }
+
+        // the sub-quest can still be completed by the event that runs out the time
+        self.status = self.q.register_event(event);
+        if self.status == QuestStatus::Ongoing && event.counts_as(self.unit) {
+            self.elapsed += 1;
+            if self.elapsed >= self.limit {
+                match self.on_timeout {
+                    OnTimeout::Fail => self.status = QuestStatus::Failed,
+                    OnTimeout::Reset => {
+                        self.q.reset();
+                        self.elapsed = 0;
+                    }
+                }
+            }
+        }
+
+        self.status
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    fn reset(&mut self) {
+        self.q.reset();
+        self.elapsed = 0;
+        self.status = self.initial_status();
+    }
+
+    fn targets(&self) -> Vec<E> {
+        if self.status == QuestStatus::Ongoing {
+            self.q.targets()
+        } else {
+            vec![]
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        let remaining = self.limit.saturating_sub(self.elapsed);
+        let description = match self.on_timeout {
+            OnTimeout::Fail => format!("Within {}:", self.unit.amount(remaining)),
+            OnTimeout::Reset => format!("Within {} (or start again):", self.unit.amount(remaining)),
+        };
+
+        QuestProgress::new(QuestKind::Within(self.unit), description, self.status)
+            .count(self.elapsed, self.limit)
+            .children(vec![self.q.progress()])
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status)
+            .counters(vec![self.elapsed])
+            .children(vec![self.q.snapshot()])
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(1, 1)?;
+        self.q.restore(&snapshot.children[0])?;
+        self.elapsed = snapshot.counters[0];
+        self.status = snapshot.status;
+        Ok(())
+    }
+}
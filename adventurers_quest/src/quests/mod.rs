@@ -5,9 +5,17 @@
 //! Primitive quests are simple quests that can be combined into a larger
 //! quest using a combinator quest
 
+pub mod avoid_block_quest;
+pub mod collect_quest;
 pub mod combinators;
+pub mod reach_quest;
+pub mod region_quest;
 pub mod walk_quest;
 pub mod walk_repeat_quest;
 
+pub use avoid_block_quest::*;
+pub use collect_quest::*;
+pub use reach_quest::*;
+pub use region_quest::*;
 pub use walk_quest::*;
 pub use walk_repeat_quest::*;
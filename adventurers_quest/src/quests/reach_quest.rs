@@ -0,0 +1,83 @@
+//! # The reach quest module
+//!
+//! The reach quest is a primitive quest that is given a target coordinate on the
+//! map, and is completed once an [`Event`] is fired that indicates the player
+//! moved onto that coordinate
+//!
+//! Unlike the walk quest, it doesn't matter which block (if any) is there
+
+use std::fmt::Display;
+
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// The state of the reach primitive quest
+#[derive(Debug)]
+pub struct ReachQuest {
+    /// The target coordinate to move onto
+    target: (i32, i32),
+    /// The status of the reach quest
+    status: QuestStatus,
+}
+
+impl ReachQuest {
+    /// Create a new reach quest given the target coordinate
+    ///
+    /// The quest starts as ongoing
+    pub fn new(target: (i32, i32)) -> Self {
+        Self {
+            target,
+            status: QuestStatus::Ongoing,
+        }
+    }
+}
+
+impl Display for ReachQuest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl Quest<Event> for ReachQuest {
+    fn register_event(&mut self, event: &Event) -> QuestStatus {
+        if let Event::Reach(x, y) = event {
+            if (*x, *y) == self.target {
+                self.status = QuestStatus::Complete;
+            }
+        }
+
+        self.status
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    fn reset(&mut self) {
+        self.status = QuestStatus::Ongoing;
+    }
+
+    fn targets(&self) -> Vec<Event> {
+        if self.status == QuestStatus::Ongoing {
+            vec![Event::Reach(self.target.0, self.target.1)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        let (x, y) = self.target;
+        QuestProgress::new(QuestKind::Reach, format!("Reach ({x}, {y})"), self.status)
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status)
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(0, 0)?;
+        self.status = snapshot.status;
+        Ok(())
+    }
+}
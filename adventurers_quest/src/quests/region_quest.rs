@@ -0,0 +1,147 @@
+//! # The region quest module
+//!
+//! The region quest is a primitive quest that is given a region of the map, and
+//! is completed once the player moves onto any coordinate within it
+//!
+//! A region is either a rectangle given by its corners, or the name of a region
+//! defined by the map. Named regions are checked with the [`Event::InRegion`]
+//! events fired by the game, so the quest doesn't need to know the map
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
+use crate::{Event, Quest, QuestStatus};
+
+/// A rectangle of coordinates, including its corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Rect {
+    /// The corner with the smallest coordinates
+    pub min: (i32, i32),
+    /// The corner with the largest coordinates
+    pub max: (i32, i32),
+}
+
+impl Rect {
+    /// Whether the coordinate is within the rectangle
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+
+    /// The coordinates within the rectangle, row by row
+    pub fn coordinates(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (self.min.1..=self.max.1).flat_map(|y| (self.min.0..=self.max.0).map(move |x| (x, y)))
+    }
+}
+
+impl Display for Rect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the area from ({}, {}) to ({}, {})",
+            self.min.0, self.min.1, self.max.0, self.max.1
+        )
+    }
+}
+
+/// A region of the map
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Region {
+    /// The coordinates within a rectangle
+    Rect(Rect),
+    /// The region with the given name, as defined by the map
+    Named(String),
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Region::Rect(rect) => write!(f, "{rect}"),
+            Region::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// The state of the region primitive quest
+#[derive(Debug)]
+pub struct RegionQuest {
+    /// The region to move into
+    region: Region,
+    /// The status of the region quest
+    status: QuestStatus,
+}
+
+impl RegionQuest {
+    /// Create a new region quest given the region to move into
+    ///
+    /// The quest starts as ongoing
+    pub fn new(region: Region) -> Self {
+        Self {
+            region,
+            status: QuestStatus::Ongoing,
+        }
+    }
+}
+
+impl Display for RegionQuest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.progress())
+    }
+}
+
+impl Quest<Event> for RegionQuest {
+    fn register_event(&mut self, event: &Event) -> QuestStatus {
+        let entered = match (&self.region, event) {
+            (Region::Rect(rect), Event::Reach(x, y)) => rect.contains((*x, *y)),
+            (Region::Named(name), Event::InRegion(region)) => name == region,
+            _ => false,
+        };
+        if entered {
+            self.status = QuestStatus::Complete;
+        }
+
+        self.status
+    }
+
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
+    fn reset(&mut self) {
+        self.status = QuestStatus::Ongoing;
+    }
+
+    fn targets(&self) -> Vec<Event> {
+        if self.status != QuestStatus::Ongoing {
+            return vec![];
+        }
+
+        match &self.region {
+            Region::Rect(rect) => rect
+                .coordinates()
+                .map(|(x, y)| Event::Reach(x, y))
+                .collect(),
+            Region::Named(name) => vec![Event::InRegion(name.clone())],
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        QuestProgress::new(
+            QuestKind::Region,
+            format!("Go to {}", self.region),
+            self.status,
+        )
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status)
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(0, 0)?;
+        self.status = snapshot.status;
+        Ok(())
+    }
+}
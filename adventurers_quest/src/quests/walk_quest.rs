@@ -10,6 +10,8 @@ use std::fmt::Display;
 
 use blocks::Block;
 
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
 use crate::{Event, Quest, QuestStatus};
 
 /// The state of the walk primitive quest
@@ -35,17 +37,14 @@ impl WalkQuest {
 
 impl Display for WalkQuest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.status {
-            QuestStatus::Complete => write!(f, "[✅] Walk on a {} block", self.target_block),
-            QuestStatus::Ongoing => write!(f, "[ ] Walk on a {} block", self.target_block),
-        }
+        write!(f, "{}", self.progress())
     }
 }
 
 impl Quest<Event> for WalkQuest {
     fn register_event(&mut self, event: &Event) -> QuestStatus {
         if self.status != QuestStatus::Complete {
-            if let Some(block) = &event.block {
+            if let Event::Move(Some(block)) = event {
                 if *block == self.target_block {
                     self.status = QuestStatus::Complete;
                 }
@@ -55,7 +54,37 @@ impl Quest<Event> for WalkQuest {
         self.status
     }
 
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
     fn reset(&mut self) {
         self.status = QuestStatus::Ongoing;
     }
+
+    fn targets(&self) -> Vec<Event> {
+        if self.status == QuestStatus::Ongoing {
+            vec![Event::on_block(self.target_block.clone())]
+        } else {
+            vec![]
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        QuestProgress::new(
+            QuestKind::Walk,
+            format!("Walk on a {} block", self.target_block),
+            self.status,
+        )
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status)
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(0, 0)?;
+        self.status = snapshot.status;
+        Ok(())
+    }
 }
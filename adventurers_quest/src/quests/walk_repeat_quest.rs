@@ -10,6 +10,8 @@ use std::fmt::Display;
 
 use blocks::Block;
 
+use crate::progress::{QuestKind, QuestProgress};
+use crate::snapshot::{QuestSnapshot, SnapshotError};
 use crate::{Event, Quest, QuestStatus};
 
 /// The state of the walk repeat primitive quest
@@ -45,34 +47,23 @@ impl WalkRepeatQuest {
 
 impl Display for WalkRepeatQuest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.status {
-            QuestStatus::Complete => write!(
-                f,
-                "[✅] Walk on exactly {} blocks of {} in a row",
-                self.target_count, self.target_block
-            ),
-            QuestStatus::Ongoing => write!(
-                f,
-                "[ ] Walk on exactly {} blocks of {} in a row",
-                self.target_count, self.target_block
-            ),
-        }
+        write!(f, "{}", self.progress())
     }
 }
 
 impl Quest<Event> for WalkRepeatQuest {
     fn register_event(&mut self, event: &Event) -> QuestStatus {
         if self.status != QuestStatus::Complete {
-            if let Some(block) = &event.block {
-                if *block == self.target_block {
+            match event {
+                Event::Move(Some(block)) if *block == self.target_block => {
                     self.blocks_walked += 1;
-                } else {
-                    // a block was walked on, but it was not the target block
+                }
+                Event::Move(_) => {
+                    // either no block was walked on, or it was not the target block
                     self.blocks_walked = 0;
                 }
-            } else {
-                // no block was walked on
-                self.blocks_walked = 0;
+                // other events do not interrupt the streak
+                _ => {}
             }
 
             if self.blocks_walked == self.target_count {
@@ -83,8 +74,43 @@ impl Quest<Event> for WalkRepeatQuest {
         self.status
     }
 
+    fn status(&self) -> QuestStatus {
+        self.status
+    }
+
     fn reset(&mut self) {
         self.blocks_walked = 0;
         self.status = QuestStatus::Ongoing;
     }
+
+    fn targets(&self) -> Vec<Event> {
+        if self.status == QuestStatus::Ongoing {
+            vec![Event::on_block(self.target_block.clone())]
+        } else {
+            vec![]
+        }
+    }
+
+    fn progress(&self) -> QuestProgress {
+        QuestProgress::new(
+            QuestKind::WalkRepeat,
+            format!(
+                "Walk on exactly {} blocks of {} in a row",
+                self.target_count, self.target_block
+            ),
+            self.status,
+        )
+        .count(self.blocks_walked, self.target_count)
+    }
+
+    fn snapshot(&self) -> QuestSnapshot {
+        QuestSnapshot::new(self.status).counters(vec![self.blocks_walked])
+    }
+
+    fn restore(&mut self, snapshot: &QuestSnapshot) -> Result<(), SnapshotError> {
+        snapshot.expect_shape(1, 0)?;
+        self.blocks_walked = snapshot.counters[0];
+        self.status = snapshot.status;
+        Ok(())
+    }
 }
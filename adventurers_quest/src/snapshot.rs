@@ -0,0 +1,87 @@
+//! # Quest snapshots
+//!
+//! A snapshot is a serializable copy of the internal state of a quest tree, such
+//! as its status and counters. Restoring a snapshot onto a quest built from the
+//! same [`QuestDef`](crate::definition::QuestDef) returns it to exactly the state
+//! the snapshot was taken in.
+
+use std::error::Error;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::QuestStatus;
+
+/// The internal state of a quest and its sub-quests
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QuestSnapshot {
+    /// The status of the quest
+    pub status: QuestStatus,
+    /// The internal counters of the quest, in an order defined by the quest
+    #[serde(default)]
+    pub counters: Vec<u32>,
+    /// The snapshots of the sub-quests, in order
+    #[serde(default)]
+    pub children: Vec<QuestSnapshot>,
+}
+
+impl QuestSnapshot {
+    /// Create a new snapshot of a quest with the given status, and no counters or sub-quests
+    pub fn new(status: QuestStatus) -> Self {
+        Self {
+            status,
+            counters: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Set the counters of the snapshot
+    pub fn counters(mut self, counters: Vec<u32>) -> Self {
+        self.counters = counters;
+        self
+    }
+
+    /// Set the sub-quest snapshots of the snapshot
+    pub fn children(mut self, children: Vec<QuestSnapshot>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Check that the snapshot has the number of counters and sub-quests expected
+    /// by the quest it is being restored onto
+    ///
+    /// # Errors
+    ///
+    /// If the number of counters or sub-quests does not match
+    pub fn expect_shape(&self, counters: usize, children: usize) -> Result<(), SnapshotError> {
+        if self.counters.len() != counters {
+            return Err(SnapshotError(format!(
+                "expected {} counters but the snapshot has {}",
+                counters,
+                self.counters.len()
+            )));
+        }
+
+        if self.children.len() != children {
+            return Err(SnapshotError(format!(
+                "expected {} sub-quests but the snapshot has {}",
+                children,
+                self.children.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// An error restoring a snapshot onto a quest with a different structure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotError(String);
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The snapshot does not match the quest: {}", self.0)
+    }
+}
+
+impl Error for SnapshotError {}
@@ -0,0 +1,114 @@
+//! # Solver module
+//!
+//! Search for a sequence of moves that completes a quest
+//!
+//! The search works with any game implementing [`Playable`], which plays moves
+//! and can be put back into an earlier state, e.g. by restoring a
+//! [`QuestSnapshot`](crate::snapshot::QuestSnapshot) of its quest. Since it
+//! searches breadth first, the moves found are as few as possible
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::QuestStatus;
+
+/// A game whose quest can be completed by searching for moves to play
+pub trait Playable {
+    /// Everything about the game that affects how it plays out from here,
+    /// including the state of the quest
+    type State: Clone + Eq + Hash;
+    /// A move the player can make
+    type Move: Copy;
+
+    /// The current state of the game
+    fn state(&self) -> Self::State;
+
+    /// Put the game back into the given state
+    fn restore(&mut self, state: &Self::State);
+
+    /// The moves worth trying from the given state
+    fn moves(&self, state: &Self::State) -> Vec<Self::Move>;
+
+    /// Play a move from the current state
+    ///
+    /// Returns [`QuestStatus::Complete`] if the quest was completed,
+    /// [`QuestStatus::Failed`] if the game can no longer be won from here, or
+    /// [`QuestStatus::Ongoing`] otherwise
+    fn play(&mut self, next_move: Self::Move) -> QuestStatus;
+}
+
+/// The result of searching for moves that complete a quest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution<M> {
+    /// The quest is completed by playing these moves from the start
+    Solved(Vec<M>),
+    /// No moves complete the quest, after exploring this many states
+    Impossible {
+        /// The number of states explored
+        states: usize,
+    },
+    /// The search explored this many states without finishing
+    GaveUp {
+        /// The number of states explored
+        states: usize,
+    },
+}
+
+/// The state each explored state was reached from and the move played, which
+/// is none for the starting state
+type Parents<S, M> = HashMap<S, Option<(S, M)>>;
+
+/// Search for the fewest moves that complete the game's quest, starting from
+/// the game's current state
+///
+/// The search gives up after exploring `max_states` states. The game is left
+/// in an unspecified state afterwards
+pub fn solve<G: Playable>(game: &mut G, max_states: usize) -> Solution<G::Move> {
+    let start = game.state();
+    let mut parents: Parents<G::State, G::Move> = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(state) = queue.pop_front() {
+        for next_move in game.moves(&state) {
+            game.restore(&state);
+            match game.play(next_move) {
+                QuestStatus::Complete => {
+                    let mut moves = path(&parents, &state);
+                    moves.push(next_move);
+                    return Solution::Solved(moves);
+                }
+                QuestStatus::Failed => continue,
+                QuestStatus::Ongoing => {}
+            }
+
+            let next = game.state();
+            if let Entry::Vacant(entry) = parents.entry(next.clone()) {
+                entry.insert(Some((state.clone(), next_move)));
+                if parents.len() > max_states {
+                    return Solution::GaveUp {
+                        states: parents.len() - 1,
+                    };
+                }
+                queue.push_back(next);
+            }
+        }
+    }
+
+    Solution::Impossible {
+        states: parents.len(),
+    }
+}
+
+/// The moves played from the start to reach the given state, following the
+/// parent of each state back to the start
+fn path<S: Eq + Hash, M: Copy>(parents: &Parents<S, M>, state: &S) -> Vec<M> {
+    let mut moves = Vec::new();
+    let mut state = state;
+    while let Some((parent, parent_move)) = &parents[state] {
+        moves.push(*parent_move);
+        state = parent;
+    }
+    moves.reverse();
+    moves
+}